@@ -1,5 +1,115 @@
-use crate::{Aig, AigEdge, AigLatch, AigNode};
-use std::{io, path::Path};
+use crate::{Aig, AigEdge, AigLatch, AigNode, AigNodeId, AigSymbols};
+use aiger::{AigerError, AigerErrorKind};
+use std::{collections::HashMap, fs::File, io, path::Path};
+
+enum Definition {
+    Input,
+    Latch,
+    And(usize),
+}
+
+/// Orders the and gates of an aiger file such that every and gate comes after
+/// the and gates it depends on.
+fn ands_topological_order(
+    defs: &HashMap<usize, Definition>,
+    and_defs: &[(usize, [aiger::Literal; 2])],
+) -> Result<Vec<usize>, AigerError> {
+    // 0: unvisited, 1: on the dfs stack, 2: finished
+    let mut state = vec![0_u8; and_defs.len()];
+    let mut order = Vec::with_capacity(and_defs.len());
+    for root in 0..and_defs.len() {
+        if state[root] != 0 {
+            continue;
+        }
+        state[root] = 1;
+        let mut stack = vec![(root, 0)];
+        while let Some(&(and, next)) = stack.last() {
+            if next == 2 {
+                state[and] = 2;
+                order.push(and);
+                stack.pop();
+                continue;
+            }
+            stack.last_mut().unwrap().1 += 1;
+            let fanin = and_defs[and].1[next].variable();
+            if let Some(Definition::And(fanin)) = defs.get(&fanin) {
+                match state[*fanin] {
+                    0 => {
+                        state[*fanin] = 1;
+                        stack.push((*fanin, 0));
+                    }
+                    1 => {
+                        return Err(AigerError::new(
+                            AigerErrorKind::CombinationalLoop,
+                            None,
+                            format!("and gate {}", and_defs[*fanin].0 * 2),
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// Correspondence between the variables of the aiger file an aig was loaded
+/// from and its node ids.
+#[derive(Debug, Clone, Default)]
+pub struct AigerVarMap {
+    origins: Vec<Option<usize>>,
+    nodes: HashMap<usize, AigNodeId>,
+}
+
+impl AigerVarMap {
+    fn new(origins: &[usize]) -> Self {
+        let nodes = origins
+            .iter()
+            .enumerate()
+            .map(|(id, var)| (*var, id))
+            .collect();
+        let origins = origins.iter().map(|var| Some(*var)).collect();
+        Self { origins, nodes }
+    }
+
+    /// Returns the node id of variable `var` of the aiger file.
+    pub fn node(&self, var: usize) -> Option<AigNodeId> {
+        self.nodes.get(&var).copied()
+    }
+
+    /// Returns the variable of the aiger file node `id` was loaded from.
+    pub fn origin(&self, id: AigNodeId) -> Option<usize> {
+        self.origins.get(id).copied().flatten()
+    }
+
+    /// Translates the map through a renumbering of the nodes, given as a map
+    /// from the old node ids to the new edges. Nodes which are dropped or
+    /// mapped to a complemented edge lose their origin.
+    pub(crate) fn remap(&self, map: &[Option<AigEdge>], num_nodes: usize) -> Self {
+        if self.origins.is_empty() {
+            return Self::default();
+        }
+        let mut origins: Vec<Option<usize>> = vec![None; num_nodes];
+        let mut nodes = HashMap::new();
+        for (id, edge) in map.iter().enumerate() {
+            if let (Some(edge), Some(Some(var))) = (edge, self.origins.get(id)) {
+                if !edge.compl() {
+                    nodes.insert(*var, edge.node_id());
+                    origins[edge.node_id()].get_or_insert(*var);
+                }
+            }
+        }
+        Self { origins, nodes }
+    }
+
+    /// Returns true if the aiger file was loaded without reencoding.
+    pub fn is_identity(&self) -> bool {
+        self.origins
+            .iter()
+            .enumerate()
+            .all(|(id, var)| *var == Some(id))
+    }
+}
 
 impl Aig {
     fn setup_levels(&mut self) {
@@ -31,71 +141,412 @@ impl Aig {
         }
     }
 
-    pub fn from_file<P: AsRef<Path>>(file: P) -> io::Result<Self> {
-        let file = std::fs::File::open(file)?;
-        let aiger = aiger::Reader::from_reader(file).unwrap();
-        let header = aiger.header();
-        let mut nodes: Vec<AigNode> = Vec::with_capacity(header.i + header.l + header.a + 1);
-        let nodes_remaining = nodes.spare_capacity_mut();
-        nodes_remaining[0].write(AigNode::new_false(0));
-        let mut inputs = Vec::new();
-        let mut latchs = Vec::new();
+    pub fn from_file<P: AsRef<Path>>(file: P) -> Result<Self, AigerError> {
+        Self::from_reader(File::open(file)?)
+    }
+
+    /// Reads an aig in aiger format. Files with unused variable indices or
+    /// with and gates which are not in topological order are reencoded into
+    /// the dense topological form: inputs first, then latches, then and
+    /// gates. The mapping from the variables of the file to node ids is kept
+    /// in `var_map`.
+    pub fn from_reader<R: io::Read>(reader: R) -> Result<Self, AigerError> {
+        let aiger = aiger::Reader::from_reader(reader)?;
+        let mut defs = HashMap::new();
+        let mut input_vars = Vec::new();
+        let mut latch_defs = Vec::new();
+        let mut and_defs = Vec::new();
         let mut outputs = Vec::new();
         let mut bads = Vec::new();
         let mut constraints = Vec::new();
-        for obj in aiger.records() {
-            let obj = obj.unwrap();
+        let mut justice = Vec::new();
+        let mut fairness = Vec::new();
+        let mut symbol_records = Vec::new();
+        let mut records = aiger.records();
+        while let Some(obj) = records.next() {
+            let obj = obj?;
+            let mut define = |l: aiger::Literal, def| {
+                if l.variable() == 0 {
+                    Err(records.error(AigerErrorKind::InvalidLiteral))
+                } else if defs.insert(l.variable(), def).is_some() {
+                    Err(records.error(AigerErrorKind::DuplicateDefinition))
+                } else {
+                    Ok(())
+                }
+            };
             match obj {
                 aiger::Aiger::Input(input) => {
-                    let id = input.0 / 2;
-                    nodes_remaining[id].write(AigNode::new_prime_input(id));
-                    inputs.push(id);
+                    define(input, Definition::Input)?;
+                    input_vars.push(input.variable());
                 }
                 aiger::Aiger::Latch {
                     output,
                     input,
                     init,
                 } => {
-                    let id = output.0 / 2;
-                    nodes_remaining[id].write(AigNode::new_latch_input(id));
-                    latchs.push(AigLatch::new(
-                        id,
-                        AigEdge::new(input.0 / 2, input.0 & 0x1 != 0),
-                        init,
-                    ));
-                }
-                aiger::Aiger::Output(o) => outputs.push(AigEdge::new(o.0 / 2, o.0 & 0x1 != 0)),
-                aiger::Aiger::BadState(b) => bads.push(AigEdge::new(b.0 / 2, b.0 & 0x1 != 0)),
-                aiger::Aiger::Constraint(c) => {
-                    constraints.push(AigEdge::new(c.0 / 2, c.0 & 0x1 != 0))
+                    define(output, Definition::Latch)?;
+                    latch_defs.push((output.variable(), input, init));
                 }
+                aiger::Aiger::Output(o) => outputs.push(o),
+                aiger::Aiger::BadState(b) => bads.push(b),
+                aiger::Aiger::Constraint(c) => constraints.push(c),
+                aiger::Aiger::Justice(j) => justice.push(j),
+                aiger::Aiger::Fairness(f) => fairness.push(f),
                 aiger::Aiger::AndGate { output, inputs } => {
-                    let id = output.0 / 2;
-                    nodes_remaining[id].write(AigNode::new_and(
-                        id,
-                        AigEdge::new(inputs[0].0 / 2, inputs[0].0 & 0x1 != 0),
-                        AigEdge::new(inputs[1].0 / 2, inputs[1].0 & 0x1 != 0),
-                        0,
-                    ));
+                    define(output, Definition::And(and_defs.len()))?;
+                    and_defs.push((output.variable(), inputs));
                 }
                 aiger::Aiger::Symbol {
-                    type_spec: _,
-                    position: _,
-                    symbol: _,
-                } => (),
+                    type_spec,
+                    position,
+                    symbol,
+                } => symbol_records.push((type_spec, position, symbol)),
+            }
+        }
+        let used = latch_defs
+            .iter()
+            .map(|(_, next, _)| next)
+            .chain(outputs.iter())
+            .chain(bads.iter())
+            .chain(constraints.iter())
+            .chain(justice.iter().flatten())
+            .chain(fairness.iter())
+            .chain(and_defs.iter().flat_map(|(_, inputs)| inputs.iter()));
+        for l in used {
+            if l.variable() != 0 && !defs.contains_key(&l.variable()) {
+                return Err(AigerError::new(
+                    AigerErrorKind::UndefinedLiteral,
+                    None,
+                    format!("literal {}", l.0),
+                ));
+            }
+        }
+        let num_nodes = defs.len() + 1;
+        let dense = defs.keys().all(|var| *var < num_nodes)
+            && and_defs
+                .iter()
+                .all(|(var, inputs)| inputs.iter().all(|l| l.variable() < *var));
+        let origins: Vec<usize> = if dense {
+            (0..num_nodes).collect()
+        } else {
+            let mut origins = vec![0];
+            origins.extend(input_vars.iter());
+            origins.extend(latch_defs.iter().map(|(var, _, _)| var));
+            let ands = ands_topological_order(&defs, &and_defs)?;
+            origins.extend(ands.iter().map(|and| and_defs[*and].0));
+            origins
+        };
+        let var_map = AigerVarMap::new(&origins);
+        let edge = |l: &aiger::Literal| AigEdge::new(var_map.nodes[&l.variable()], l.is_inverted());
+        let mut nodes = Vec::with_capacity(num_nodes);
+        nodes.push(AigNode::new_false(0));
+        for (id, var) in origins.iter().enumerate().skip(1) {
+            nodes.push(match defs[var] {
+                Definition::Input => AigNode::new_prime_input(id),
+                Definition::Latch => AigNode::new_latch_input(id),
+                Definition::And(and) => {
+                    let inputs = &and_defs[and].1;
+                    AigNode::new_and(id, edge(&inputs[0]), edge(&inputs[1]), 0)
+                }
+            });
+        }
+        let inputs: Vec<AigNodeId> = input_vars.iter().map(|var| var_map.nodes[var]).collect();
+        let latchs: Vec<AigLatch> = latch_defs
+            .iter()
+            .map(|(var, next, init)| AigLatch::new(var_map.nodes[var], edge(next), *init))
+            .collect();
+        let mut symbols = AigSymbols::default();
+        for (type_spec, position, symbol) in symbol_records {
+            match type_spec {
+                aiger::Symbol::Input => {
+                    if let Some(id) = inputs.get(position) {
+                        symbols.nodes.insert(*id, symbol);
+                    }
+                }
+                aiger::Symbol::Latch => {
+                    if let Some(l) = latchs.get(position) {
+                        symbols.nodes.insert(l.input, symbol);
+                    }
+                }
+                aiger::Symbol::Output => {
+                    symbols.outputs.insert(position, symbol);
+                }
+                aiger::Symbol::BadState => {
+                    symbols.bads.insert(position, symbol);
+                }
+                aiger::Symbol::Constraint => {
+                    symbols.constraints.insert(position, symbol);
+                }
+                aiger::Symbol::Justice => {
+                    symbols.justice.insert(position, symbol);
+                }
+                aiger::Symbol::Fairness => {
+                    symbols.fairness.insert(position, symbol);
+                }
             }
         }
-        unsafe { nodes.set_len(header.i + header.l + header.a + 1) };
         let mut ret = Self {
             nodes,
             inputs,
             latchs,
-            outputs,
-            bads,
-            constraints,
+            outputs: outputs.iter().map(edge).collect(),
+            bads: bads.iter().map(edge).collect(),
+            constraints: constraints.iter().map(edge).collect(),
+            justice: justice
+                .iter()
+                .map(|j| j.iter().map(edge).collect())
+                .collect(),
+            fairness: fairness.iter().map(edge).collect(),
+            symbols,
+            var_map,
+            strash: Default::default(),
         };
         ret.setup_levels();
         ret.setup_fanouts();
+        ret.setup_strash();
         Ok(ret)
     }
+
+    /// Writes the aig in aiger format. Node ids are reencoded densely: inputs
+    /// first, then latches, then the and nodes in the fanin cone of latch
+    /// nexts, outputs, bads, constraints, justice and fairness. Nodes which
+    /// are neither inputs nor and nodes but are still referenced (e.g. latch
+    /// inputs transferred into prime inputs) are written as extra inputs.
+    pub fn write_aiger<W: io::Write>(&self, writer: W, binary: bool) -> io::Result<W> {
+        let cone = self.fanin_logic_cone(&self.roots());
+        let mut declared = vec![false; self.num_nodes()];
+        for id in self
+            .inputs
+            .iter()
+            .chain(self.latchs.iter().map(|l| &l.input))
+        {
+            declared[*id] = true;
+        }
+        let mut inputs = self.inputs.clone();
+        for id in self.nodes_range() {
+            if cone[id] && !declared[id] && !self.nodes[id].is_and() {
+                inputs.push(id);
+            }
+        }
+        let ands: Vec<AigNodeId> = self
+            .nodes_range()
+            .filter(|id| cone[*id] && self.nodes[*id].is_and())
+            .collect();
+        let mut map = vec![0; self.num_nodes()];
+        let ids = inputs
+            .iter()
+            .chain(self.latchs.iter().map(|l| &l.input))
+            .chain(ands.iter());
+        for (var, id) in ids.enumerate() {
+            map[*id] = var + 1;
+        }
+        let lit = |e: AigEdge| aiger::Literal::from_variable(map[e.node_id()], e.compl());
+        let header = aiger::Header {
+            m: inputs.len() + self.latchs.len() + ands.len(),
+            i: inputs.len(),
+            l: self.latchs.len(),
+            o: self.outputs.len(),
+            a: ands.len(),
+            b: self.bads.len(),
+            c: self.constraints.len(),
+            j: self.justice.len(),
+            f: self.fairness.len(),
+            binary,
+        };
+        let mut writer = aiger::Writer::from_writer(writer, header)?;
+        for input in inputs.iter() {
+            writer.write_record(&aiger::Aiger::Input(lit((*input).into())))?;
+        }
+        for latch in self.latchs.iter() {
+            writer.write_record(&aiger::Aiger::Latch {
+                output: lit(latch.input.into()),
+                input: lit(latch.next),
+                init: latch.init,
+            })?;
+        }
+        for output in self.outputs.iter() {
+            writer.write_record(&aiger::Aiger::Output(lit(*output)))?;
+        }
+        for bad in self.bads.iter() {
+            writer.write_record(&aiger::Aiger::BadState(lit(*bad)))?;
+        }
+        for constraint in self.constraints.iter() {
+            writer.write_record(&aiger::Aiger::Constraint(lit(*constraint)))?;
+        }
+        for justice in self.justice.iter() {
+            writer.write_record(&aiger::Aiger::Justice(
+                justice.iter().map(|j| lit(*j)).collect(),
+            ))?;
+        }
+        for fairness in self.fairness.iter() {
+            writer.write_record(&aiger::Aiger::Fairness(lit(*fairness)))?;
+        }
+        for and in ands.iter() {
+            let node = &self.nodes[*and];
+            writer.write_record(&aiger::Aiger::AndGate {
+                output: lit((*and).into()),
+                inputs: [lit(node.fanin0()), lit(node.fanin1())],
+            })?;
+        }
+        let mut symbols = Vec::new();
+        for (position, input) in inputs.iter().enumerate() {
+            if let Some(name) = self.symbols.nodes.get(input) {
+                symbols.push((aiger::Symbol::Input, position, name));
+            }
+        }
+        for (position, latch) in self.latchs.iter().enumerate() {
+            if let Some(name) = self.symbols.nodes.get(&latch.input) {
+                symbols.push((aiger::Symbol::Latch, position, name));
+            }
+        }
+        for (type_spec, names, len) in [
+            (
+                aiger::Symbol::Output,
+                &self.symbols.outputs,
+                self.outputs.len(),
+            ),
+            (aiger::Symbol::BadState, &self.symbols.bads, self.bads.len()),
+            (
+                aiger::Symbol::Constraint,
+                &self.symbols.constraints,
+                self.constraints.len(),
+            ),
+            (
+                aiger::Symbol::Justice,
+                &self.symbols.justice,
+                self.justice.len(),
+            ),
+            (
+                aiger::Symbol::Fairness,
+                &self.symbols.fairness,
+                self.fairness.len(),
+            ),
+        ] {
+            for position in 0..len {
+                if let Some(name) = names.get(&position) {
+                    symbols.push((type_spec, position, name));
+                }
+            }
+        }
+        for (type_spec, position, symbol) in symbols {
+            writer.write_record(&aiger::Aiger::Symbol {
+                type_spec,
+                position,
+                symbol: symbol.clone(),
+            })?;
+        }
+        writer.into_inner()
+    }
+
+    pub fn to_aag<P: AsRef<Path>>(&self, file: P) -> io::Result<()> {
+        let file = File::create(file)?;
+        self.write_aiger(file, false)?;
+        Ok(())
+    }
+
+    pub fn to_aig<P: AsRef<Path>>(&self, file: P) -> io::Result<()> {
+        let file = File::create(file)?;
+        self.write_aiger(file, true)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{aig, truth, truths};
+
+    #[test]
+    fn read_dense() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 4 2 1 1 1\n",
+            "2\n",
+            "4\n",
+            "6 8 1\n",
+            "8\n",
+            "8 2 7\n",
+        ));
+
+        assert!(aig.var_map.is_identity());
+        assert_eq!(aig.inputs, vec![1, 2]);
+        assert_eq!(aig.latchs[0].input, 3);
+        assert_eq!(aig.latchs[0].init, Some(true));
+        assert_eq!(aig.outputs, vec![AigEdge::new(4, false)]);
+        assert_eq!(
+            truth(&aig, aig.outputs[0]),
+            0xaaaaaaaaaaaaaaaa & !0xf0f0f0f0f0f0f0f0
+        );
+    }
+
+    #[test]
+    fn read_sparse_non_topological() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 10 2 1 1 2\n",
+            "2\n",
+            "10\n",
+            "14 18\n",
+            "18\n",
+            "18 20 2\n",
+            "20 10 15\n",
+        ));
+
+        assert!(!aig.var_map.is_identity());
+        assert_eq!(aig.num_nodes(), 6);
+        assert_eq!(aig.inputs, vec![1, 2]);
+        assert_eq!(aig.latchs[0].input, 3);
+        for (var, id) in [(1, 1), (5, 2), (7, 3), (10, 4), (9, 5)] {
+            assert_eq!(aig.var_map.node(var), Some(id));
+            assert_eq!(aig.var_map.origin(id), Some(var));
+        }
+        assert_eq!(aig.outputs, vec![AigEdge::new(5, false)]);
+        assert_eq!(aig.latchs[0].next, aig.outputs[0]);
+        let expected = 0xaaaaaaaaaaaaaaaa & 0xcccccccccccccccc & !0xf0f0f0f0f0f0f0f0;
+        assert_eq!(truth(&aig, aig.outputs[0]), expected);
+    }
+
+    #[test]
+    fn read_undefined_literal() {
+        let e = Aig::from_reader("aag 2 1 0 1 0\n2\n4\n".as_bytes()).unwrap_err();
+        assert_eq!(e.kind, AigerErrorKind::UndefinedLiteral);
+    }
+
+    #[test]
+    fn read_combinational_loop() {
+        #[rustfmt::skip]
+        let e = Aig::from_reader(concat!(
+            "aag 2 0 0 1 2\n",
+            "2\n",
+            "2 4 4\n",
+            "4 2 2\n",
+        ).as_bytes()).unwrap_err();
+        assert_eq!(e.kind, AigerErrorKind::CombinationalLoop);
+    }
+
+    #[test]
+    fn write_read_roundtrip() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 10 2 1 1 2 1\n",
+            "2\n",
+            "10\n",
+            "14 18 0\n",
+            "18\n",
+            "15\n",
+            "18 20 2\n",
+            "20 10 15\n",
+            "i0 a\n",
+            "l0 l\n",
+        ));
+        for binary in [false, true] {
+            let written = aig.write_aiger(Vec::new(), binary).unwrap();
+            let read = Aig::from_reader(written.as_slice()).unwrap();
+            assert_eq!(read.num_nodes(), aig.num_nodes());
+            assert_eq!(read.latchs[0].init, Some(false));
+            assert_eq!(truths(&read), truths(&aig));
+            assert_eq!(read.node_name(read.inputs[0]), Some("a"));
+            assert_eq!(read.node_name(read.latchs[0].input), Some("l"));
+        }
+    }
 }
@@ -0,0 +1,172 @@
+use crate::{Aig, AigEdge, AigLatch, AigNode, AigNodeId};
+
+pub(crate) fn map_edge(map: &[Option<AigEdge>], e: AigEdge) -> AigEdge {
+    map[e.node_id()].unwrap().not_if(e.compl())
+}
+
+impl Aig {
+    /// Copies the nodes marked in `cone` into `aig` in topological order.
+    /// `map` gives the edges of `aig` the inputs and latches are copied to
+    /// and is filled in for the other nodes: unmapped nodes which are no and
+    /// nodes, e.g. latch inputs transferred into prime inputs, become new
+    /// inputs of `aig`, and and nodes are rebuilt by `build` from the edges
+    /// the smaller nodes were copied to.
+    pub(crate) fn copy_cone_with<F: FnMut(&mut Aig, &[Option<AigEdge>], AigNodeId) -> AigEdge>(
+        &self,
+        aig: &mut Aig,
+        cone: &[bool],
+        map: &mut [Option<AigEdge>],
+        mut build: F,
+    ) {
+        map[0] = Some(AigEdge::constant_edge(false));
+        for id in self.nodes_range() {
+            if cone[id] && map[id].is_none() && !self.nodes[id].is_and() {
+                map[id] = Some(aig.new_input_node().into());
+            }
+        }
+        for id in self.nodes_range() {
+            if cone[id] && self.nodes[id].is_and() {
+                map[id] = Some(build(aig, map, id));
+            }
+        }
+    }
+
+    /// Copies and node `id` into `aig` with the fanins `map` gives.
+    pub(crate) fn copy_and(
+        &self,
+        aig: &mut Aig,
+        map: &[Option<AigEdge>],
+        id: AigNodeId,
+    ) -> AigEdge {
+        let fanin0 = map_edge(map, self.nodes[id].fanin0());
+        let fanin1 = map_edge(map, self.nodes[id].fanin1());
+        aig.new_and_node(fanin0, fanin1)
+    }
+
+    /// Copies the nodes marked in `cone` into `aig` as
+    /// [`Aig::copy_cone_with`] does, with the and nodes copied as they are.
+    pub(crate) fn copy_cone(&self, aig: &mut Aig, cone: &[bool], map: &mut [Option<AigEdge>]) {
+        self.copy_cone_with(aig, cone, map, |aig, map, id| self.copy_and(aig, map, id));
+    }
+
+    /// Builds a new aig with the same inputs and latches in the same order,
+    /// where the and nodes in the fanin cone of the roots are rebuilt in
+    /// topological order by `build`, see [`Aig::copy_cone_with`]. Returns the
+    /// new aig and a map from the node ids of `self` to the edges of the new
+    /// aig, which is `None` for dropped nodes.
+    pub(crate) fn reconstruct<F: FnMut(&mut Aig, &[Option<AigEdge>], AigNodeId) -> AigEdge>(
+        &self,
+        build: F,
+    ) -> (Aig, Vec<Option<AigEdge>>) {
+        let cone = self.fanin_logic_cone(&self.roots());
+        let mut map = vec![None; self.num_nodes()];
+        let mut aig = Aig::new();
+        for input in self.inputs.iter() {
+            map[*input] = Some(aig.new_input_node().into());
+        }
+        for l in self.latchs.iter() {
+            let id = aig.num_nodes();
+            aig.nodes.push(AigNode::new_latch_input(id));
+            map[l.input] = Some(id.into());
+        }
+        self.copy_cone_with(&mut aig, &cone, &mut map, build);
+        aig.latchs = self
+            .latchs
+            .iter()
+            .map(|l| {
+                AigLatch::new(
+                    map[l.input].unwrap().node_id(),
+                    map_edge(&map, l.next),
+                    l.init,
+                )
+            })
+            .collect();
+        let map_edges = |edges: &[AigEdge]| edges.iter().map(|e| map_edge(&map, *e)).collect();
+        aig.outputs = map_edges(&self.outputs);
+        aig.bads = map_edges(&self.bads);
+        aig.constraints = map_edges(&self.constraints);
+        aig.justice = self.justice.iter().map(|j| map_edges(j)).collect();
+        aig.fairness = map_edges(&self.fairness);
+        aig.symbols = self.symbols.clone();
+        aig.symbols.nodes = self
+            .symbols
+            .nodes
+            .iter()
+            .filter(|(id, _)| !self.nodes[**id].is_and())
+            .filter_map(|(id, name)| Some((map[*id]?.node_id(), name.clone())))
+            .collect();
+        aig.var_map = self.var_map.remap(&map, aig.num_nodes());
+        (aig, map)
+    }
+
+    /// Removes the dead and nodes left by merges and the and nodes outside
+    /// the fanin cone of the roots, merges structurally identical nodes and
+    /// renumbers the nodes densely in topological order. Inputs and latches
+    /// keep their order. Returns a map from the old node ids to the new
+    /// edges, which is `None` for removed nodes.
+    pub fn cleanup(&mut self) -> Vec<Option<AigEdge>> {
+        let (aig, map) = self.reconstruct(|aig, map, id| self.copy_and(aig, map, id));
+        *self = aig;
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{aig, truths};
+    use crate::AigEdge;
+
+    #[test]
+    fn cleanup_removes_dead_nodes() {
+        #[rustfmt::skip]
+        let mut aig = aig(concat!(
+            "aag 7 2 1 1 4\n",
+            "2\n",
+            "4\n",
+            "6 12\n",
+            "14\n",
+            "8 2 4\n",
+            "10 2 6\n",
+            "12 8 6\n",
+            "14 12 4\n",
+        ));
+        let expected = truths(&aig);
+
+        let map = aig.cleanup();
+        assert_eq!(aig.num_nodes(), 7);
+        assert_eq!(truths(&aig), expected);
+        assert_eq!(aig.inputs, vec![1, 2]);
+        assert_eq!(aig.latchs[0].input, 3);
+        assert_eq!(map[5], None);
+        assert_eq!(map[6], Some(AigEdge::new(5, false)));
+        assert_eq!(aig.var_map.origin(5), Some(6));
+        assert_eq!(aig.var_map.node(6), Some(5));
+    }
+
+    #[test]
+    fn cleanup_after_merge() {
+        #[rustfmt::skip]
+        let mut aig = aig(concat!(
+            "aag 7 2 1 1 4\n",
+            "2\n",
+            "4\n",
+            "6 12\n",
+            "14\n",
+            "8 2 4\n",
+            "10 2 6\n",
+            "12 8 6\n",
+            "14 12 4\n",
+        ));
+        let expected = truths(&aig);
+
+        aig.merge_fe_node(AigEdge::new(7, false), AigEdge::new(6, false));
+        let map = aig.cleanup();
+        assert_eq!(aig.num_nodes(), 6);
+        assert_eq!(truths(&aig), expected);
+        assert_eq!(map[7], None);
+        assert_eq!(aig.outputs[0], AigEdge::new(5, false));
+        assert_eq!(aig.latchs[0].next, aig.outputs[0]);
+        assert_eq!(aig.var_map.origin(5), Some(6));
+        assert_eq!(aig.var_map.node(7), None);
+    }
+}
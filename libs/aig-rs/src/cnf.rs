@@ -1,6 +1,33 @@
-use crate::{Aig, AigEdge, AigNodeId};
+use crate::{cut::isop, Aig, AigEdge, AigNodeId};
 use logic_form::{Clause, Cnf, Lit};
-use std::collections::HashSet;
+use std::{collections::HashSet, str::FromStr};
+
+/// CNF encodings of the and nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CnfEncoding {
+    /// Three clauses per and node.
+    #[default]
+    Tseitin,
+    /// Multi-input and blocks.
+    Block,
+    /// Technology mapping over 4-feasible cuts.
+    Cut,
+}
+
+impl FromStr for CnfEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tseitin" => Ok(Self::Tseitin),
+            "block" => Ok(Self::Block),
+            "cut" => Ok(Self::Cut),
+            _ => Err(format!(
+                "unknown cnf encoding {s}, expected tseitin, block or cut"
+            )),
+        }
+    }
+}
 
 impl Aig {
     pub fn get_optimized_cnf(&self, logic: &[AigEdge]) -> Cnf {
@@ -83,64 +110,182 @@ impl Aig {
         ans
     }
 
-    fn rec_get_block(
-        &self,
-        node: AigNodeId,
-        block: &mut Vec<AigEdge>,
-        visit: &mut HashSet<AigNodeId>,
-    ) {
-        if visit.contains(&node) {
-            return;
+    /// Leaves of the multi-input and block of `node`, which spans its and
+    /// tree through uncomplemented fanins.
+    fn get_block(&self, node: AigNodeId) -> Vec<AigEdge> {
+        let mut block = Vec::new();
+        let mut visit = HashSet::new();
+        let mut stack = vec![self.nodes[node].fanin1(), self.nodes[node].fanin0()];
+        while let Some(fanin) = stack.pop() {
+            if !visit.insert(fanin) {
+                continue;
+            }
+            if !fanin.compl() && self.nodes[fanin.node_id()].is_and() {
+                stack.push(self.nodes[fanin.node_id()].fanin1());
+                stack.push(self.nodes[fanin.node_id()].fanin0());
+            } else {
+                block.push(fanin);
+            }
         }
-        visit.insert(node);
-        if self.nodes[node].is_and() {
-            let mut closure = |fanin: AigEdge| {
-                if fanin.compl() {
-                    if !visit.contains(&fanin.node_id()) {
-                        visit.insert(fanin.node_id());
-                        block.push(fanin);
-                    }
-                } else {
-                    self.rec_get_block(fanin.node_id(), block, visit);
-                }
-            };
-            closure(self.nodes[node].fanin0());
-            closure(self.nodes[node].fanin1());
-        } else {
-            block.push(node.into());
+        block
+    }
+
+    /// Encodes every multi-input and block in the fanin cone of `logic` by
+    /// one clause per leaf and one long clause.
+    pub fn get_block_optimized_cnf(&self, logic: &[AigEdge]) -> Cnf {
+        let mut refs = vec![false; self.num_nodes()];
+        for l in logic {
+            refs[l.node_id()] = true;
+        }
+        let mut cnf = Cnf::new();
+        for node in self.nodes_range().rev() {
+            if !refs[node] || !self.nodes[node].is_and() {
+                continue;
+            }
+            let node_lit = AigEdge::new(node, false).to_lit();
+            let mut clause = Clause::from([node_lit]);
+            for block_node in self.get_block(node) {
+                refs[block_node.node_id()] = true;
+                let block_node = block_node.to_lit();
+                cnf.push(Clause::from([!node_lit, block_node]));
+                clause.push(!block_node);
+            }
+            cnf.push(clause);
         }
+        cnf
     }
 
-    fn rec_get_block_cnf(&self, node: AigNodeId, visit: &mut HashSet<AigNodeId>, cnf: &mut Cnf) {
-        if visit.contains(&node) {
-            return;
+    /// Technology mapping based encoding. The fanin cone of `logic` is
+    /// covered by 4-feasible cuts chosen by area flow, where the area of a
+    /// cut is the number of its clauses, and every cut is encoded by the
+    /// irredundant sums of products of its function and of its complement.
+    pub fn get_cut_cnf(&self, logic: &[AigEdge]) -> Cnf {
+        let cone = self.fanin_logic_cone(logic);
+        let cuts = self.enumerate_cuts(&cone);
+        let refs = self.references();
+        let mut flow = vec![0.0; self.num_nodes()];
+        let mut best = vec![0; self.num_nodes()];
+        for node in self.nodes_range() {
+            if !cone[node] || !self.nodes[node].is_and() {
+                continue;
+            }
+            flow[node] = f64::INFINITY;
+            for (i, cut) in cuts[node].iter().enumerate() {
+                if cut.leaves == [node] {
+                    continue;
+                }
+                let area = isop(cut.truth).len() + isop(!cut.truth).len();
+                let cut_flow = cut
+                    .leaves
+                    .iter()
+                    .map(|l| flow[*l] / refs[*l].max(1) as f64)
+                    .sum::<f64>()
+                    + area as f64;
+                if cut_flow < flow[node] {
+                    flow[node] = cut_flow;
+                    best[node] = i;
+                }
+            }
         }
-        visit.insert(node);
-        if !self.nodes[node].is_and() {
-            return;
+        let mut needed = vec![false; self.num_nodes()];
+        for l in logic {
+            needed[l.node_id()] = true;
         }
-        let mut block = Vec::new();
-        self.rec_get_block(node, &mut block, &mut HashSet::new());
-        let node_lit = AigEdge::new(node, false).to_lit();
-        let mut clause = Clause::from([node_lit]);
-        for block_node in block {
-            self.rec_get_block_cnf(block_node.node_id(), visit, cnf);
-            let block_node = block_node.to_lit();
-            cnf.push(Clause::from([!node_lit, block_node]));
-            clause.push(!block_node);
+        let mut cnf = Cnf::new();
+        for node in self.nodes_range().rev() {
+            if !needed[node] || !self.nodes[node].is_and() {
+                continue;
+            }
+            let cut = &cuts[node][best[node]];
+            for l in cut.leaves.iter() {
+                needed[*l] = true;
+            }
+            let node_lit = AigEdge::new(node, false).to_lit();
+            for (truth, lit) in [(cut.truth, node_lit), (!cut.truth, !node_lit)] {
+                for cube in isop(truth) {
+                    let mut clause = Clause::from([lit]);
+                    for (var, value) in cube {
+                        clause.push(AigEdge::new(cut.leaves[var], value).to_lit());
+                    }
+                    cnf.push(clause);
+                }
+            }
         }
-        cnf.push(clause);
+        cnf
     }
 
-    pub fn get_block_optimized_cnf(&self) -> Cnf {
-        let mut cnf = Cnf::new();
-        let mut visit = HashSet::new();
-        for l in self.latchs.iter() {
-            self.rec_get_block_cnf(l.next.node_id(), &mut visit, &mut cnf);
+    /// Encodes the fanin cone of `logic` with `encoding`.
+    pub fn get_encoded_cnf(&self, logic: &[AigEdge], encoding: CnfEncoding) -> Cnf {
+        match encoding {
+            CnfEncoding::Tseitin => self.get_optimized_cnf(logic),
+            CnfEncoding::Block => self.get_block_optimized_cnf(logic),
+            CnfEncoding::Cut => self.get_cut_cnf(logic),
         }
-        for bad in self.bads.iter() {
-            self.rec_get_block_cnf(bad.node_id(), &mut visit, &mut cnf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::aig;
+    use minisat::{SatResult, Solver};
+
+    #[test]
+    fn encodings_agree_with_simulation() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 10 4 0 2 6\n",
+            "2\n",
+            "4\n",
+            "6\n",
+            "8\n",
+            "16\n",
+            "21\n",
+            "10 2 5\n",
+            "12 7 9\n",
+            "14 10 13\n",
+            "16 14 2\n",
+            "18 16 11\n",
+            "20 19 8\n",
+        ));
+        for encoding in [CnfEncoding::Tseitin, CnfEncoding::Block, CnfEncoding::Cut] {
+            let mut solver = Solver::new();
+            while solver.num_var() < aig.num_nodes() {
+                solver.new_var();
+            }
+            solver.add_clause(&[AigEdge::constant_edge(true).to_lit()]);
+            for clause in aig.get_encoded_cnf(&aig.outputs, encoding).iter() {
+                solver.add_clause(clause);
+            }
+            for minterm in 0..16_u64 {
+                let values =
+                    aig.simulate_word(|id| if minterm >> (id - 1) & 1 == 1 { !0 } else { 0 });
+                let inputs: Vec<Lit> = aig
+                    .inputs
+                    .iter()
+                    .map(|i| AigEdge::new(*i, values[*i] == 0).to_lit())
+                    .collect();
+                for output in aig.outputs.iter() {
+                    let value = (values[output.node_id()] != 0) != output.compl();
+                    let mut assumps = inputs.clone();
+                    assumps.push(output.not_if(!value).to_lit());
+                    assert!(
+                        matches!(solver.solve(&assumps), SatResult::Sat(_)),
+                        "{encoding:?}"
+                    );
+                    *assumps.last_mut().unwrap() = output.not_if(value).to_lit();
+                    assert!(
+                        matches!(solver.solve(&assumps), SatResult::Unsat(_)),
+                        "{encoding:?}"
+                    );
+                }
+            }
         }
-        cnf
+    }
+
+    #[test]
+    fn encoding_from_str() {
+        assert_eq!("block".parse(), Ok(CnfEncoding::Block));
+        assert!("dimacs".parse::<CnfEncoding>().is_err());
     }
 }
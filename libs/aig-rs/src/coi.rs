@@ -0,0 +1,137 @@
+use crate::{cleanup::map_edge, Aig, AigEdge, AigLatch, AigNode, AigNodeId};
+
+impl Aig {
+    /// Marks the sequential cone of influence of `roots`: the nodes in their
+    /// fanin cone, the fanin cones of the nexts of every latch in it, and so
+    /// on until a fixed point.
+    pub fn fanin_sequential_cone(&self, roots: &[AigEdge]) -> Vec<bool> {
+        let mut latch_of = vec![None; self.num_nodes()];
+        for (i, l) in self.latchs.iter().enumerate() {
+            latch_of[l.input] = Some(i);
+        }
+        let mut flag = vec![false; self.num_nodes()];
+        let mut stack: Vec<AigNodeId> = roots.iter().map(|e| e.node_id()).collect();
+        while let Some(id) = stack.pop() {
+            if flag[id] {
+                continue;
+            }
+            flag[id] = true;
+            if self.nodes[id].is_and() {
+                stack.push(self.nodes[id].fanin0().node_id());
+                stack.push(self.nodes[id].fanin1().node_id());
+            } else if let Some(l) = latch_of[id] {
+                stack.push(self.latchs[l].next.node_id());
+            }
+        }
+        flag
+    }
+
+    /// Cone of influence reduction. Builds an aig with `property` as the only
+    /// bad, which keeps the constraints and only the inputs, latches and and
+    /// nodes in the sequential cone of influence of the property and the
+    /// constraints. Returns the reduced aig and a map from the node ids of
+    /// `self` to the edges of the reduced aig.
+    pub fn coi_refine(&self, property: AigEdge) -> (Aig, Vec<Option<AigEdge>>) {
+        let mut roots = vec![property];
+        roots.extend(self.constraints.iter());
+        let cone = self.fanin_sequential_cone(&roots);
+        let mut map = vec![None; self.num_nodes()];
+        let mut aig = Aig::new();
+        for input in self.inputs.iter() {
+            if cone[*input] {
+                map[*input] = Some(aig.new_input_node().into());
+            }
+        }
+        for l in self.latchs.iter() {
+            if cone[l.input] {
+                let id = aig.num_nodes();
+                aig.nodes.push(AigNode::new_latch_input(id));
+                map[l.input] = Some(id.into());
+            }
+        }
+        self.copy_cone(&mut aig, &cone, &mut map);
+        for l in self.latchs.iter() {
+            if cone[l.input] {
+                let input = map[l.input].unwrap().node_id();
+                aig.latchs
+                    .push(AigLatch::new(input, map_edge(&map, l.next), l.init));
+            }
+        }
+        aig.bads.push(map_edge(&map, property));
+        aig.constraints = self
+            .constraints
+            .iter()
+            .map(|c| map_edge(&map, *c))
+            .collect();
+        for (id, name) in self.symbols.nodes.iter() {
+            if let Some(edge) = map[*id] {
+                if !self.nodes[*id].is_and() {
+                    aig.symbols.nodes.insert(edge.node_id(), name.clone());
+                }
+            }
+        }
+        aig.var_map = self.var_map.remap(&map, aig.num_nodes());
+        (aig, map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{aig, truths};
+
+    #[test]
+    fn coi_drops_latchs_and_inputs_outside_the_cone() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 6 2 3 0 1 1\n",
+            "2\n",
+            "4\n",
+            "6 10\n",
+            "8 4\n",
+            "10 11\n",
+            "12\n",
+            "12 6 2\n",
+        ));
+        let (coi, map) = aig.coi_refine(aig.bads[0]);
+
+        assert_eq!(coi.inputs.len(), 1);
+        assert_eq!(coi.latchs.len(), 2);
+        assert!(map[aig.inputs[1]].is_none());
+        assert!(map[aig.latchs[1].input].is_none());
+        let origins: Vec<Option<usize>> = coi
+            .latchs
+            .iter()
+            .map(|l| coi.var_map.origin(l.input))
+            .collect();
+        assert_eq!(origins, vec![Some(3), Some(5)]);
+        assert_eq!(
+            truths(&coi),
+            vec![
+                0xaaaaaaaaaaaaaaaa & 0xcccccccccccccccc,
+                0xf0f0f0f0f0f0f0f0,
+                !0xf0f0f0f0f0f0f0f0,
+            ]
+        );
+    }
+
+    #[test]
+    fn coi_keeps_the_cone_of_constraints() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 6 2 3 0 1 1 1\n",
+            "2\n",
+            "4\n",
+            "6 10\n",
+            "8 4\n",
+            "10 11\n",
+            "12\n",
+            "4\n",
+            "12 6 2\n",
+        ));
+        let (coi, _) = aig.coi_refine(aig.bads[0]);
+
+        assert_eq!(coi.inputs.len(), 2);
+        assert_eq!(coi.latchs.len(), 2);
+        assert_eq!(coi.constraints.len(), 1);
+    }
+}
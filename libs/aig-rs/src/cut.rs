@@ -0,0 +1,229 @@
+use crate::{Aig, AigNodeId};
+
+pub(crate) const CUT_SIZE: usize = 4;
+const MAX_CUTS: usize = 8;
+
+/// Truth tables of the four cut variables.
+pub(crate) const VAR_TRUTH: [u16; CUT_SIZE] = [0xaaaa, 0xcccc, 0xf0f0, 0xff00];
+
+/// Cofactor of a truth table with respect to a variable, as a truth table
+/// which does not depend on the variable.
+pub(crate) fn cofactor(truth: u16, var: usize, value: bool) -> u16 {
+    let shift = 1 << var;
+    if value {
+        let t = truth & VAR_TRUTH[var];
+        t | t >> shift
+    } else {
+        let t = truth & !VAR_TRUTH[var];
+        t | t << shift
+    }
+}
+
+/// Irredundant sum of products of a truth table, computed by the
+/// Minato-Morreale algorithm. Cubes are lists of (variable, value) pairs.
+pub(crate) fn isop(truth: u16) -> Vec<Vec<(usize, bool)>> {
+    let mut cubes = Vec::new();
+    isop_rec(truth, truth, &mut cubes);
+    cubes
+}
+
+/// Appends the cubes of an irredundant cover of some function between
+/// `lower` and `upper` and returns the function.
+fn isop_rec(lower: u16, upper: u16, cubes: &mut Vec<Vec<(usize, bool)>>) -> u16 {
+    if lower == 0 {
+        return 0;
+    }
+    if upper == !0 {
+        cubes.push(Vec::new());
+        return !0;
+    }
+    let var = (0..CUT_SIZE)
+        .rev()
+        .find(|v| {
+            cofactor(lower, *v, false) != cofactor(lower, *v, true)
+                || cofactor(upper, *v, false) != cofactor(upper, *v, true)
+        })
+        .unwrap();
+    let (lower0, lower1) = (cofactor(lower, var, false), cofactor(lower, var, true));
+    let (upper0, upper1) = (cofactor(upper, var, false), cofactor(upper, var, true));
+    let start = cubes.len();
+    let f0 = isop_rec(lower0 & !upper1, upper0, cubes);
+    let middle = cubes.len();
+    let f1 = isop_rec(lower1 & !upper0, upper1, cubes);
+    for (i, cube) in cubes[start..].iter_mut().enumerate() {
+        cube.push((var, start + i >= middle));
+    }
+    let f = isop_rec((lower0 & !f0) | (lower1 & !f1), upper0 & upper1, cubes);
+    (f0 & !VAR_TRUTH[var]) | (f1 & VAR_TRUTH[var]) | f
+}
+
+/// A cut of a node with at most four leaves, sorted by node id, and the truth
+/// table of the node over them, where leaf `i` is variable `i`.
+#[derive(Debug, Clone)]
+pub(crate) struct Cut {
+    pub leaves: Vec<AigNodeId>,
+    pub truth: u16,
+}
+
+impl Cut {
+    fn trivial(id: AigNodeId) -> Self {
+        Self {
+            leaves: vec![id],
+            truth: VAR_TRUTH[0],
+        }
+    }
+
+    /// Truth table of the cut over a superset of its leaves.
+    fn expand(&self, leaves: &[AigNodeId]) -> u16 {
+        let position: Vec<usize> = self
+            .leaves
+            .iter()
+            .map(|l| leaves.iter().position(|x| x == l).unwrap())
+            .collect();
+        let mut truth = 0;
+        for minterm in 0..16 {
+            let mut sub = 0;
+            for (i, p) in position.iter().enumerate() {
+                if minterm >> p & 1 == 1 {
+                    sub |= 1 << i;
+                }
+            }
+            if self.truth >> sub & 1 == 1 {
+                truth |= 1 << minterm;
+            }
+        }
+        truth
+    }
+
+    fn dominates(&self, other: &Cut) -> bool {
+        self.leaves.iter().all(|l| other.leaves.contains(l))
+    }
+}
+
+fn merge_leaves(x: &[AigNodeId], y: &[AigNodeId]) -> Option<Vec<AigNodeId>> {
+    let mut leaves = x.to_vec();
+    for l in y {
+        if !leaves.contains(l) {
+            leaves.push(*l);
+        }
+    }
+    leaves.sort();
+    (leaves.len() <= CUT_SIZE).then_some(leaves)
+}
+
+impl Aig {
+    /// Enumerates the 4-feasible cuts of the nodes in `cone`. Every node keeps
+    /// at most eight non-dominated cuts, the smallest first, followed by its
+    /// trivial cut.
+    pub(crate) fn enumerate_cuts(&self, cone: &[bool]) -> Vec<Vec<Cut>> {
+        let mut cuts: Vec<Vec<Cut>> = vec![Vec::new(); self.num_nodes()];
+        for id in self.nodes_range_with_false() {
+            if !cone[id] {
+                continue;
+            }
+            if !self.nodes[id].is_and() {
+                cuts[id].push(Cut::trivial(id));
+                continue;
+            }
+            let fanin0 = self.nodes[id].fanin0();
+            let fanin1 = self.nodes[id].fanin1();
+            let mut node_cuts: Vec<Cut> = Vec::new();
+            for c0 in cuts[fanin0.node_id()].iter() {
+                for c1 in cuts[fanin1.node_id()].iter() {
+                    let Some(leaves) = merge_leaves(&c0.leaves, &c1.leaves) else {
+                        continue;
+                    };
+                    let mut cut = Cut { leaves, truth: 0 };
+                    if node_cuts.iter().any(|c| c.dominates(&cut)) {
+                        continue;
+                    }
+                    node_cuts.retain(|c| !cut.dominates(c));
+                    let t0 = c0.expand(&cut.leaves);
+                    let t1 = c1.expand(&cut.leaves);
+                    let t0 = if fanin0.compl() { !t0 } else { t0 };
+                    let t1 = if fanin1.compl() { !t1 } else { t1 };
+                    cut.truth = t0 & t1;
+                    node_cuts.push(cut);
+                }
+            }
+            node_cuts.sort_by_key(|c| c.leaves.len());
+            node_cuts.truncate(MAX_CUTS);
+            node_cuts.push(Cut::trivial(id));
+            cuts[id] = node_cuts;
+        }
+        cuts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::aig;
+
+    #[test]
+    fn isop_covers_truth_table() {
+        for truth in 0..=u16::MAX {
+            let cover = isop(truth).iter().fold(0, |cover, cube| {
+                cover
+                    | cube.iter().fold(!0, |c, (var, value)| {
+                        c & if *value {
+                            VAR_TRUTH[*var]
+                        } else {
+                            !VAR_TRUTH[*var]
+                        }
+                    })
+            });
+            assert_eq!(cover, truth);
+        }
+    }
+
+    #[test]
+    fn cofactor_drops_variable() {
+        for truth in [0x8000, 0x6996, 0xcafe] {
+            for (var, var_truth) in VAR_TRUTH.iter().enumerate() {
+                for value in [false, true] {
+                    let c = cofactor(truth, var, value);
+                    assert_eq!(cofactor(c, var, !value), c);
+                    let t = if value { *var_truth } else { !var_truth };
+                    assert_eq!(c & t, truth & t);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cut_truth_tables_match_simulation() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 8 4 0 1 4\n",
+            "2\n",
+            "4\n",
+            "6\n",
+            "8\n",
+            "16\n",
+            "10 2 5\n",
+            "12 7 9\n",
+            "14 10 13\n",
+            "16 14 2\n",
+        ));
+        let cuts = aig.enumerate_cuts(&vec![true; aig.num_nodes()]);
+        let values = aig.simulate_word(|id| match id {
+            1..=4 => VAR_TRUTH[id - 1] as u64,
+            _ => 0,
+        });
+        for id in aig.nodes_range() {
+            assert!(cuts[id].len() > 1 || !aig.nodes[id].is_and());
+            for cut in cuts[id].iter() {
+                for minterm in 0..16 {
+                    let index: usize = cut
+                        .leaves
+                        .iter()
+                        .enumerate()
+                        .map(|(i, l)| ((values[*l] >> minterm & 1) as usize) << i)
+                        .sum();
+                    assert_eq!(cut.truth >> index & 1, (values[id] >> minterm & 1) as u16);
+                }
+            }
+        }
+    }
+}
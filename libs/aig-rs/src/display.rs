@@ -37,14 +37,20 @@ impl Display for Aig {
         write!(f, "inputs:")?;
         for input in &self.inputs {
             write!(f, " {}", self.nodes[*input])?;
+            if let Some(name) = self.node_name(*input) {
+                write!(f, "({})", name)?;
+            }
         }
         writeln!(f, "\n------------------")?;
         writeln!(f, "latchs:")?;
         for latch in &self.latchs {
+            write!(f, "input: {}", self.nodes[latch.input])?;
+            if let Some(name) = self.node_name(latch.input) {
+                write!(f, "({})", name)?;
+            }
             writeln!(
                 f,
-                "input: {}, next: {}{}",
-                self.nodes[latch.input],
+                ", next: {}{}",
                 latch.next,
                 self.nodes[latch.next.node_id()]
             )?;
@@ -66,24 +72,32 @@ impl Display for Aig {
         writeln!(f, "------------------")?;
         writeln!(f, "outputs:")?;
         for idx in 0..self.outputs.len() {
-            writeln!(
+            write!(
                 f,
                 "O{}: {}{}",
                 idx + 1,
                 self.outputs[idx],
                 self.nodes[self.outputs[idx].node_id()]
             )?;
+            match self.symbols.outputs.get(&idx) {
+                Some(name) => writeln!(f, "({})", name)?,
+                None => writeln!(f)?,
+            }
         }
         writeln!(f, "------------------")?;
         writeln!(f, "bad states:")?;
         for idx in 0..self.bads.len() {
-            writeln!(
+            write!(
                 f,
                 "B{}: {}{}",
                 idx + 1,
                 self.bads[idx],
                 self.nodes[self.bads[idx].node_id()]
             )?;
+            match self.symbols.bads.get(&idx) {
+                Some(name) => writeln!(f, "({})", name)?,
+                None => writeln!(f)?,
+            }
         }
         writeln!(f, "==================")?;
         Ok(())
@@ -0,0 +1,211 @@
+use crate::{Aig, AigEdge, AigNodeId};
+use logic_form::Lit;
+use minisat::{SatResult, Solver};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+
+const FRAIG_SIM_WORDS: usize = 4;
+
+struct FraigClasses {
+    classes: Vec<Vec<AigNodeId>>,
+    class_of: Vec<Option<usize>>,
+    phase: Vec<bool>,
+}
+
+impl FraigClasses {
+    fn new(aig: &Aig, cone: &[bool], signatures: &[Vec<u64>]) -> Self {
+        let phase: Vec<bool> = signatures.iter().map(|s| s[0] & 1 == 1).collect();
+        let mut map: HashMap<Vec<u64>, Vec<AigNodeId>> = HashMap::new();
+        for id in aig.nodes_range_with_false() {
+            if id == 0 || cone[id] {
+                let signature = if phase[id] {
+                    signatures[id].iter().map(|w| !w).collect()
+                } else {
+                    signatures[id].clone()
+                };
+                map.entry(signature).or_default().push(id);
+            }
+        }
+        let mut classes: Vec<Vec<AigNodeId>> = map.into_values().filter(|c| c.len() > 1).collect();
+        classes.sort();
+        let mut class_of = vec![None; aig.num_nodes()];
+        for (i, class) in classes.iter().enumerate() {
+            for id in class {
+                class_of[*id] = Some(i);
+            }
+        }
+        Self {
+            classes,
+            class_of,
+            phase,
+        }
+    }
+
+    /// Splits every class by the values of a counterexample.
+    fn refine(&mut self, values: &[u64]) {
+        for i in 0..self.classes.len() {
+            let head = self.classes[i][0];
+            let value = |id: AigNodeId| (values[id] & 1 == 1) != self.phase[id];
+            let head_value = value(head);
+            let (same, diff): (Vec<AigNodeId>, Vec<AigNodeId>) = self.classes[i]
+                .iter()
+                .partition(|id| value(**id) == head_value);
+            if diff.is_empty() {
+                continue;
+            }
+            for (class, members) in [(i, same), (self.classes.len(), diff)] {
+                for id in members.iter() {
+                    self.class_of[*id] = (members.len() > 1).then_some(class);
+                }
+                if class == i {
+                    self.classes[i] = members;
+                } else {
+                    self.classes.push(members);
+                }
+            }
+        }
+    }
+}
+
+impl Aig {
+    /// Returns the values of the leaves in a pattern which distinguishes
+    /// `x` and `y`, or `None` if they are equivalent.
+    fn fraig_prove(
+        solver: &mut Solver,
+        leaves: &[AigNodeId],
+        x: Lit,
+        y: Lit,
+    ) -> Option<HashMap<AigNodeId, bool>> {
+        for assumps in [[x, !y], [!x, y]] {
+            if let SatResult::Sat(model) = solver.solve(&assumps) {
+                return Some(
+                    leaves
+                        .iter()
+                        .map(|id| (*id, model.lit_value(AigEdge::from(*id).to_lit())))
+                        .collect(),
+                );
+            }
+        }
+        None
+    }
+
+    /// SAT sweeping. Candidate equivalent nodes are found by random
+    /// simulation, where latches are free variables, and proved or refuted
+    /// with a sat solver. Proved nodes are merged into the smallest node of
+    /// their class. Returns the number of merged nodes.
+    pub fn fraig(&mut self) -> usize {
+        let roots = self.roots();
+        let cone = self.fanin_logic_cone(&roots);
+        let leaves: Vec<AigNodeId> = self
+            .nodes_range()
+            .filter(|id| cone[*id] && !self.nodes[*id].is_and())
+            .collect();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut signatures = vec![Vec::with_capacity(FRAIG_SIM_WORDS); self.num_nodes()];
+        for _ in 0..FRAIG_SIM_WORDS {
+            let values = self.simulate_word(|_| rng.gen());
+            for (signature, value) in signatures.iter_mut().zip(values) {
+                signature.push(value);
+            }
+        }
+        let mut classes = FraigClasses::new(self, &cone, &signatures);
+        let mut solver = Solver::new();
+        while solver.num_var() < self.num_nodes() {
+            solver.new_var();
+        }
+        solver.add_clause(&[AigEdge::constant_edge(true).to_lit()]);
+        for clause in self.get_optimized_cnf(&roots).iter() {
+            solver.add_clause(clause);
+        }
+        let mut root_nodes: HashSet<AigNodeId> = roots.iter().map(|e| e.node_id()).collect();
+        let mut num_merged = 0;
+        for id in self.nodes_range() {
+            if !self.nodes[id].is_and() {
+                continue;
+            }
+            while let Some(class) = classes.class_of[id] {
+                let in_use = |id: &AigNodeId| {
+                    !self.nodes[*id].is_and()
+                        || !self.nodes[*id].fanouts.is_empty()
+                        || root_nodes.contains(id)
+                };
+                if !in_use(&id) {
+                    break;
+                }
+                let repr = classes.classes[class].iter().copied().find(in_use).unwrap();
+                if repr == id {
+                    break;
+                }
+                let by = AigEdge::new(repr, classes.phase[id] != classes.phase[repr]);
+                let x = AigEdge::from(id).to_lit();
+                match Self::fraig_prove(&mut solver, &leaves, x, by.to_lit()) {
+                    Some(cex) => {
+                        let values =
+                            self.simulate_word(
+                                |id| if cex.get(&id) == Some(&true) { !0 } else { 0 },
+                            );
+                        classes.refine(&values);
+                    }
+                    None => {
+                        self.merge_fe_node(id.into(), by);
+                        root_nodes = self.roots().iter().map(|e| e.node_id()).collect();
+                        num_merged += 1;
+                        break;
+                    }
+                }
+            }
+        }
+        num_merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{aig, truths};
+    use crate::AigEdge;
+
+    #[test]
+    fn fraig_merges_equivalent_nodes() {
+        #[rustfmt::skip]
+        let mut aig = aig(concat!(
+            "aag 11 3 0 3 8\n",
+            "2\n",
+            "4\n",
+            "6\n",
+            "10\n",
+            "14\n",
+            "23\n",
+            "8 2 4\n",
+            "10 8 6\n",
+            "12 4 6\n",
+            "14 2 12\n",
+            "16 3 5\n",
+            "18 2 5\n",
+            "20 9 19\n",
+            "22 20 20\n",
+        ));
+        let expected = truths(&aig);
+
+        assert!(aig.fraig() >= 2);
+        assert_eq!(truths(&aig), expected);
+        assert_eq!(aig.outputs[0], aig.outputs[1]);
+        assert_eq!(aig.outputs[2], AigEdge::new(aig.inputs[0], false));
+    }
+
+    #[test]
+    fn fraig_keeps_latchs_free() {
+        #[rustfmt::skip]
+        let mut aig = aig(concat!(
+            "aag 3 1 1 1 1\n",
+            "2\n",
+            "4 0 0\n",
+            "6\n",
+            "6 2 4\n",
+        ));
+        let expected = truths(&aig);
+
+        assert_eq!(aig.fraig(), 0);
+        assert_eq!(truths(&aig), expected);
+        assert_eq!(aig.outputs[0], AigEdge::new(3, false));
+    }
+}
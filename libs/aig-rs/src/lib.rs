@@ -1,17 +1,39 @@
 mod aiger;
+mod cec;
+mod cleanup;
 mod cnf;
+mod coi;
+mod cut;
 mod display;
+mod fraig;
 mod logic_form;
+mod miter;
 mod others;
+mod rewrite;
+mod simulate;
+mod strash;
+mod symbol;
 mod ternary;
-
+mod unroll;
+mod witness;
+
+pub use crate::aiger::AigerVarMap;
+pub use cec::*;
+pub use cnf::CnfEncoding;
+pub use miter::*;
+pub use simulate::*;
+pub use symbol::*;
 pub use ternary::*;
+pub use unroll::*;
+pub use witness::*;
 
 pub use crate::logic_form::*;
+use crate::strash::{and_simplify, Strash};
+pub use ::aiger::{AigerError, AigerErrorKind};
 use ::logic_form::Lit;
 use std::{
     cmp::Reverse,
-    collections::BinaryHeap,
+    collections::{BinaryHeap, HashMap},
     mem::{swap, take},
     ops::{Index, Not, Range},
     vec,
@@ -231,6 +253,11 @@ pub struct Aig {
     pub outputs: Vec<AigEdge>,
     pub bads: Vec<AigEdge>,
     pub constraints: Vec<AigEdge>,
+    pub justice: Vec<Vec<AigEdge>>,
+    pub fairness: Vec<AigEdge>,
+    pub symbols: AigSymbols,
+    pub var_map: AigerVarMap,
+    strash: Strash,
 }
 
 impl Aig {
@@ -242,6 +269,11 @@ impl Aig {
             outputs: Vec::new(),
             bads: Vec::new(),
             constraints: Vec::new(),
+            justice: Vec::new(),
+            fairness: Vec::new(),
+            symbols: AigSymbols::default(),
+            var_map: AigerVarMap::default(),
+            strash: Strash::new(),
         }
     }
 
@@ -258,38 +290,27 @@ impl Aig {
         if fanin0.node_id() > fanin1.node_id() {
             swap(&mut fanin0, &mut fanin1);
         }
-        if fanin0 == AigEdge::constant_edge(true) {
-            return fanin1;
-        }
-        if fanin0 == AigEdge::constant_edge(false) {
-            return AigEdge::constant_edge(false);
-        }
-        if fanin1 == AigEdge::constant_edge(true) {
-            return fanin0;
+        if let Some(edge) = and_simplify(fanin0, fanin1) {
+            return edge;
         }
-        if fanin1 == AigEdge::constant_edge(false) {
-            return AigEdge::constant_edge(false);
-        }
-        if fanin0 == fanin1 {
-            fanin0
-        } else if fanin0 == !fanin1 {
-            AigEdge::constant_edge(false)
-        } else {
-            let nodeid = self.nodes.len();
-            let level = self.nodes[fanin0.node_id()]
-                .level
-                .max(self.nodes[fanin1.node_id()].level)
-                + 1;
-            let and = AigNode::new_and(nodeid, fanin0, fanin1, level);
-            self.nodes.push(and);
-            self.nodes[fanin0.id]
-                .fanouts
-                .push(AigEdge::new(nodeid, fanin0.compl()));
-            self.nodes[fanin1.id]
-                .fanouts
-                .push(AigEdge::new(nodeid, fanin1.compl()));
-            nodeid.into()
+        if let Some(nodeid) = self.strash.get(&(fanin0, fanin1)) {
+            return (*nodeid).into();
         }
+        let nodeid = self.nodes.len();
+        let level = self.nodes[fanin0.node_id()]
+            .level
+            .max(self.nodes[fanin1.node_id()].level)
+            + 1;
+        let and = AigNode::new_and(nodeid, fanin0, fanin1, level);
+        self.nodes.push(and);
+        self.nodes[fanin0.id]
+            .fanouts
+            .push(AigEdge::new(nodeid, fanin0.compl()));
+        self.nodes[fanin1.id]
+            .fanouts
+            .push(AigEdge::new(nodeid, fanin1.compl()));
+        self.strash.insert((fanin0, fanin1), nodeid);
+        nodeid.into()
     }
 
     pub fn new_or_node(&mut self, fanin0: AigEdge, fanin1: AigEdge) -> AigEdge {
@@ -315,29 +336,73 @@ impl Aig {
             let new_node = self.new_and_node(peek0, peek1);
             heap.push(Reverse((self.nodes[new_node.node_id()].level, new_node)));
         }
-        heap.pop().unwrap().0 .1
+        heap.pop()
+            .map_or(AigEdge::constant_edge(true), |Reverse((_, edge))| edge)
     }
 
+    /// Replaces node `replaced` by the equivalent edge `by`. Fanouts which
+    /// become trivial or structurally identical to another node are merged
+    /// in turn.
     pub fn merge_fe_node(&mut self, replaced: AigEdge, by: AigEdge) {
-        let compl = replaced.compl() != by.compl();
-        let replaced = replaced.node_id();
-        let by = by.node_id();
-        assert!(replaced > by);
-        self.nodes[by].fanouts.retain(|e| e.node_id() != replaced);
-        let fanouts = take(&mut self.nodes[replaced].fanouts);
+        assert!(replaced.node_id() > by.node_id());
+        let mut merged: HashMap<AigNodeId, AigEdge> = HashMap::new();
+        let find = |merged: &HashMap<AigNodeId, AigEdge>, mut e: AigEdge| {
+            while let Some(by) = merged.get(&e.node_id()) {
+                e = if e.compl() { !*by } else { *by };
+            }
+            e
+        };
+        let mut pending = vec![(replaced, by)];
+        while let Some((x, y)) = pending.pop() {
+            let x = find(&merged, x);
+            let y = find(&merged, y);
+            if x.node_id() == y.node_id() {
+                debug_assert_eq!(x, y);
+                continue;
+            }
+            let (replaced, by) = if x.node_id() > y.node_id() {
+                (x, y)
+            } else {
+                (y, x)
+            };
+            let by = if replaced.compl() { !by } else { by };
+            merged.insert(replaced.node_id(), by);
+            self.replace_node(replaced.node_id(), by, &mut pending);
+        }
+    }
+
+    fn replace_node(
+        &mut self,
+        replaced: AigNodeId,
+        by: AigEdge,
+        pending: &mut Vec<(AigEdge, AigEdge)>,
+    ) {
+        if self.nodes[replaced].is_and() {
+            self.strash_remove(replaced);
+            let fanin0 = self.nodes[replaced].fanin0().node_id();
+            let fanin1 = self.nodes[replaced].fanin1().node_id();
+            self.nodes[fanin0]
+                .fanouts
+                .retain(|e| e.node_id() != replaced);
+            self.nodes[fanin1]
+                .fanouts
+                .retain(|e| e.node_id() != replaced);
+        }
+        let mut fanouts = take(&mut self.nodes[replaced].fanouts);
+        fanouts.sort_by_key(|e| e.node_id());
+        fanouts.dedup_by_key(|e| e.node_id());
         for fanout in fanouts {
             let fanout_node_id = fanout.node_id();
+            self.strash_remove(fanout_node_id);
             let mut fanin0 = self.nodes[fanout_node_id].fanin0();
             let mut fanin1 = self.nodes[fanout_node_id].fanin1();
-            assert!(fanin0.node_id() < fanin1.node_id());
-            // self.strash.remove(fanin0, fanin1);
-            if fanin0.node_id() == replaced {
-                assert_eq!(fanout.compl(), fanin0.compl());
-                fanin0 = AigEdge::new(by, fanout.compl() ^ compl);
-            }
-            if fanin1.node_id() == replaced {
-                assert_eq!(fanout.compl(), fanin1.compl());
-                fanin1 = AigEdge::new(by, fanout.compl() ^ compl);
+            for fanin in [&mut fanin0, &mut fanin1] {
+                if fanin.node_id() == replaced {
+                    *fanin = if fanin.compl() { !by } else { by };
+                    self.nodes[by.node_id()]
+                        .fanouts
+                        .push(AigEdge::new(fanout_node_id, fanin.compl()));
+                }
             }
             if fanin0.node_id() > fanin1.node_id() {
                 swap(&mut fanin0, &mut fanin1);
@@ -349,32 +414,23 @@ impl Aig {
                 .level
                 .max(self.nodes[fanin1.node_id()].level)
                 + 1;
-            self.nodes[by].fanouts.push(fanout);
-        }
-        for latch in &mut self.latchs {
-            if latch.next.node_id() == replaced {
-                latch.next.set_nodeid(by);
-                if compl {
-                    latch.next = !latch.next;
-                }
+            if let Some(equal) = self.strash_insert(fanout_node_id) {
+                pending.push((fanout_node_id.into(), equal));
             }
         }
-        for out in &mut self.outputs {
-            if out.node_id() == replaced {
-                out.set_nodeid(by);
-                if compl {
-                    *out = !*out
-                }
+        let replace = |e: &mut AigEdge| {
+            if e.node_id() == replaced {
+                *e = if e.compl() { !by } else { by };
             }
-        }
-        for bad in &mut self.bads {
-            if bad.node_id() == replaced {
-                bad.set_nodeid(by);
-                if compl {
-                    *bad = !*bad
-                }
-            }
-        }
+        };
+        self.latchs.iter_mut().for_each(|l| replace(&mut l.next));
+        self.outputs
+            .iter_mut()
+            .chain(self.bads.iter_mut())
+            .chain(self.constraints.iter_mut())
+            .chain(self.justice.iter_mut().flatten())
+            .chain(self.fairness.iter_mut())
+            .for_each(replace);
     }
 }
 
@@ -467,3 +523,153 @@ impl Index<AigNodeId> for Aig {
         &self.nodes[index]
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{Aig, AigEdge, AigNodeId};
+
+    pub(crate) fn aig(s: &str) -> Aig {
+        Aig::from_reader(s.as_bytes()).unwrap()
+    }
+
+    /// Truth table of `e` over the inputs and latches, which get the
+    /// patterns of the first six variables by position.
+    pub(crate) fn truth(aig: &Aig, e: AigEdge) -> u64 {
+        const PATTERNS: [u64; 6] = [
+            0xaaaaaaaaaaaaaaaa,
+            0xcccccccccccccccc,
+            0xf0f0f0f0f0f0f0f0,
+            0xff00ff00ff00ff00,
+            0xffff0000ffff0000,
+            0xffffffff00000000,
+        ];
+        let leafs: Vec<AigNodeId> = aig
+            .inputs
+            .iter()
+            .copied()
+            .chain(aig.latchs.iter().map(|l| l.input))
+            .collect();
+        let values = aig.simulate_word(|id| {
+            leafs
+                .iter()
+                .position(|l| *l == id)
+                .map_or(0, |i| PATTERNS[i])
+        });
+        values[e.node_id()] ^ if e.compl() { !0 } else { 0 }
+    }
+
+    /// Truth tables of the outputs, bads, constraints and latch nexts.
+    pub(crate) fn truths(aig: &Aig) -> Vec<u64> {
+        aig.outputs
+            .iter()
+            .chain(aig.bads.iter())
+            .chain(aig.constraints.iter())
+            .chain(aig.latchs.iter().map(|l| &l.next))
+            .map(|e| truth(aig, *e))
+            .collect()
+    }
+
+    /// Every hashed and node has the fanins of its key, and every and node in
+    /// the fanin cone of the outputs is hashed.
+    fn assert_strash(aig: &Aig) {
+        for ((fanin0, fanin1), id) in aig.strash.iter() {
+            assert!(aig.nodes[*id].is_and());
+            assert_eq!(
+                (aig.nodes[*id].fanin0(), aig.nodes[*id].fanin1()),
+                (*fanin0, *fanin1)
+            );
+        }
+        let cone = aig.fanin_logic_cone(&aig.outputs);
+        for id in aig
+            .nodes_range()
+            .filter(|id| cone[*id] && aig.nodes[*id].is_and())
+        {
+            let key = (aig.nodes[id].fanin0(), aig.nodes[id].fanin1());
+            assert_eq!(aig.strash.get(&key), Some(&id));
+        }
+    }
+
+    #[test]
+    fn strash_reuses_and_nodes() {
+        let mut aig = Aig::new();
+        let a: AigEdge = aig.new_input_node().into();
+        let b: AigEdge = aig.new_input_node().into();
+        let and = aig.new_and_node(a, b);
+        assert_eq!(aig.new_and_node(b, a), and);
+        assert_eq!(aig.new_or_node(!a, !b), !and);
+        assert_ne!(aig.new_and_node(!a, b), and);
+        assert_eq!(aig.num_nodes(), 5);
+        aig.outputs = vec![and];
+        assert_strash(&aig);
+    }
+
+    #[test]
+    fn strash_folds_trivial_ands() {
+        let mut aig = Aig::new();
+        let a: AigEdge = aig.new_input_node().into();
+        assert_eq!(aig.new_and_node(a, a), a);
+        assert_eq!(aig.new_and_node(!a, !a), !a);
+        assert_eq!(aig.new_and_node(a, !a), AigEdge::constant_edge(false));
+        assert_eq!(aig.new_and_node(a, AigEdge::constant_edge(true)), a);
+        let f = AigEdge::constant_edge(false);
+        assert_eq!(aig.new_and_node(f, !a), f);
+        assert_eq!(aig.new_or_node(a, !a), AigEdge::constant_edge(true));
+        assert_eq!(aig.num_nodes(), 2);
+        assert!(aig.strash.is_empty());
+    }
+
+    #[test]
+    fn merge_rehashes_fanouts() {
+        #[rustfmt::skip]
+        let mut aig = aig(concat!(
+            "aag 8 4 0 3 4\n",
+            "2\n",
+            "4\n",
+            "6\n",
+            "8\n",
+            "14\n",
+            "16\n",
+            "13\n",
+            "10 2 4\n",
+            "12 2 6\n",
+            "14 10 8\n",
+            "16 12 8\n",
+        ));
+        aig.merge_fe_node(AigEdge::new(3, false), AigEdge::new(2, false));
+        assert_eq!(
+            aig.outputs,
+            vec![
+                AigEdge::new(7, false),
+                AigEdge::new(7, false),
+                AigEdge::new(5, true)
+            ]
+        );
+        assert_strash(&aig);
+        assert_eq!(aig.strash.len(), 2);
+        let fanouts = |id: AigNodeId| -> Vec<AigNodeId> {
+            aig.nodes[id].fanouts.iter().map(|e| e.node_id()).collect()
+        };
+        assert_eq!(fanouts(1), vec![5]);
+        assert_eq!(fanouts(2), vec![5]);
+        assert_eq!(fanouts(4), vec![7]);
+    }
+
+    #[test]
+    fn merge_folds_fanouts() {
+        #[rustfmt::skip]
+        let mut aig = aig(concat!(
+            "aag 4 2 0 2 2\n",
+            "2\n",
+            "4\n",
+            "8\n",
+            "9\n",
+            "6 2 4\n",
+            "8 6 2\n",
+        ));
+        aig.merge_fe_node(AigEdge::new(2, false), AigEdge::new(1, true));
+        let f = AigEdge::constant_edge(false);
+        assert_eq!(aig.outputs, vec![f, !f]);
+        assert_strash(&aig);
+        assert!(aig.strash.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{cleanup::map_edge, Aig, AigEdge, AigLatch, AigNode};
+use std::{collections::HashMap, fmt};
+
+/// How the inputs of the two aigs of a miter are matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiterInputs {
+    /// The `i`th inputs are shared.
+    ByPosition,
+    /// Inputs with the same symbol name are shared.
+    ByName,
+}
+
+/// An error which occurs when the two aigs of a miter do not fit together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MiterError {
+    /// The aigs have different numbers of inputs.
+    InputCount(usize, usize),
+    /// The aigs have different numbers of outputs.
+    OutputCount(usize, usize),
+    /// The aigs have different numbers of latches.
+    LatchCount(usize, usize),
+    /// An input has no symbol name, identified by the aig (0 or 1) and its
+    /// position.
+    UnnamedInput(usize, usize),
+    /// An input name occurs more than once or only in one of the aigs.
+    UnmatchedInput(String),
+}
+
+impl fmt::Display for MiterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MiterError::InputCount(x, y) => write!(f, "input count mismatch: {x} and {y}"),
+            MiterError::OutputCount(x, y) => write!(f, "output count mismatch: {x} and {y}"),
+            MiterError::LatchCount(x, y) => write!(f, "latch count mismatch: {x} and {y}"),
+            MiterError::UnnamedInput(aig, i) => write!(f, "input {i} of aig {aig} has no name"),
+            MiterError::UnmatchedInput(name) => write!(f, "input `{name}` can not be matched"),
+        }
+    }
+}
+
+impl std::error::Error for MiterError {}
+
+impl Aig {
+    /// Copies the aig into `aig` with its inputs driven by `inputs` and returns
+    /// the map from its node ids to the edges of `aig`.
+    fn copy_into(&self, aig: &mut Aig, inputs: &[AigEdge]) -> Vec<Option<AigEdge>> {
+        let mut map = vec![None; self.num_nodes()];
+        for (input, edge) in self.inputs.iter().zip(inputs.iter()) {
+            map[*input] = Some(*edge);
+        }
+        for l in self.latchs.iter() {
+            let id = aig.num_nodes();
+            aig.nodes.push(AigNode::new_latch_input(id));
+            map[l.input] = Some(id.into());
+        }
+        self.copy_cone(aig, &self.fanin_logic_cone(&self.roots()), &mut map);
+        for l in self.latchs.iter() {
+            aig.latchs.push(AigLatch::new(
+                map[l.input].unwrap().node_id(),
+                map_edge(&map, l.next),
+                l.init,
+            ));
+        }
+        aig.constraints
+            .extend(self.constraints.iter().map(|c| map_edge(&map, *c)));
+        map
+    }
+
+    /// Builds the sequential miter of two aigs: the product machine with
+    /// shared inputs, matched as given by `inputs`, the latches and
+    /// constraints of both, and a single bad which is the or of the xors of
+    /// the pairwise outputs. The inputs keep the order and names of `self`.
+    pub fn miter(&self, other: &Aig, inputs: MiterInputs) -> Result<Aig, MiterError> {
+        if self.inputs.len() != other.inputs.len() {
+            return Err(MiterError::InputCount(
+                self.inputs.len(),
+                other.inputs.len(),
+            ));
+        }
+        if self.outputs.len() != other.outputs.len() {
+            return Err(MiterError::OutputCount(
+                self.outputs.len(),
+                other.outputs.len(),
+            ));
+        }
+        let mut aig = Aig::new();
+        let shared: Vec<AigEdge> = self
+            .inputs
+            .iter()
+            .map(|_| aig.new_input_node().into())
+            .collect();
+        let other_inputs = match inputs {
+            MiterInputs::ByPosition => shared.clone(),
+            MiterInputs::ByName => {
+                let mut names = HashMap::new();
+                for (i, input) in self.inputs.iter().enumerate() {
+                    let name = self
+                        .node_name(*input)
+                        .ok_or(MiterError::UnnamedInput(0, i))?;
+                    if names.insert(name, shared[i]).is_some() {
+                        return Err(MiterError::UnmatchedInput(name.to_string()));
+                    }
+                }
+                let mut other_inputs = Vec::with_capacity(other.inputs.len());
+                for (i, input) in other.inputs.iter().enumerate() {
+                    let name = other
+                        .node_name(*input)
+                        .ok_or(MiterError::UnnamedInput(1, i))?;
+                    let edge = names
+                        .remove(name)
+                        .ok_or_else(|| MiterError::UnmatchedInput(name.to_string()))?;
+                    other_inputs.push(edge);
+                }
+                other_inputs
+            }
+        };
+        for (input, edge) in self.inputs.iter().zip(shared.iter()) {
+            if let Some(name) = self.node_name(*input) {
+                aig.symbols.nodes.insert(edge.node_id(), name.to_string());
+            }
+        }
+        let map = self.copy_into(&mut aig, &shared);
+        let other_map = other.copy_into(&mut aig, &other_inputs);
+        let equals: Vec<AigEdge> = self
+            .outputs
+            .iter()
+            .zip(other.outputs.iter())
+            .map(|(x, y)| {
+                let x = map_edge(&map, *x);
+                let y = map_edge(&other_map, *y);
+                aig.new_equal_node(x, y)
+            })
+            .collect();
+        let bad = if equals.is_empty() {
+            AigEdge::constant_edge(false)
+        } else {
+            !aig.new_and_nodes(equals)
+        };
+        aig.bads.push(bad);
+        Ok(aig)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{aig, truth};
+    use crate::AigSimulator;
+
+    const COMB: &str = "aag 3 2 0 1 1\n2\n4\n6\n6 2 5\ni0 a\ni1 b\n";
+    const COMB_SWAPPED: &str = "aag 3 2 0 1 1\n2\n4\n6\n6 4 3\ni0 b\ni1 a\n";
+
+    /// Whether the bad of the miter is hit within a few cycles of random
+    /// simulation.
+    fn bad_hit(miter: &Aig) -> bool {
+        let mut simulator = AigSimulator::new(miter, 0);
+        (0..16).any(|_| {
+            simulator.step_random();
+            simulator.edge_value(miter.bads[0]) != 0
+        })
+    }
+
+    #[test]
+    fn miter_of_equal_aigs() {
+        #[rustfmt::skip]
+        let x = aig(concat!(
+            "aag 3 1 1 1 1\n",
+            "2\n",
+            "4 7 0\n",
+            "6\n",
+            "6 2 4\n",
+        ));
+        let miter = x.miter(&x, MiterInputs::ByPosition).unwrap();
+
+        assert_eq!(miter.inputs.len(), 1);
+        assert_eq!(miter.latchs.len(), 2);
+        assert_eq!(miter.bads.len(), 1);
+        assert!(!bad_hit(&miter));
+    }
+
+    #[test]
+    fn miter_of_different_aigs() {
+        let x = aig("aag 3 1 1 1 1\n2\n4 7 0\n6\n6 2 4\n");
+        let y = aig("aag 3 1 1 1 1\n2\n4 6 0\n6\n6 2 4\n");
+        let miter = x.miter(&y, MiterInputs::ByPosition).unwrap();
+
+        assert!(bad_hit(&miter));
+    }
+
+    #[test]
+    fn miter_inputs_by_name() {
+        let x = aig(COMB);
+        let y = aig(COMB_SWAPPED);
+
+        let miter = x.miter(&y, MiterInputs::ByName).unwrap();
+        assert_eq!(truth(&miter, miter.bads[0]), 0);
+        assert_eq!(miter.node_name(miter.inputs[0]), Some("a"));
+        let miter = x.miter(&y, MiterInputs::ByPosition).unwrap();
+        assert_ne!(truth(&miter, miter.bads[0]), 0);
+    }
+
+    #[test]
+    fn miter_errors() {
+        let x = aig(COMB);
+        let unnamed = aig("aag 3 2 0 1 1\n2\n4\n6\n6 2 5\n");
+        let one_input = aig("aag 1 1 0 1 0\n2\n2\n");
+
+        assert_eq!(
+            x.miter(&one_input, MiterInputs::ByPosition).unwrap_err(),
+            MiterError::InputCount(2, 1)
+        );
+        assert_eq!(
+            x.miter(&unnamed, MiterInputs::ByName).unwrap_err(),
+            MiterError::UnnamedInput(1, 0)
+        );
+    }
+}
@@ -8,4 +8,26 @@ impl Aig {
                 .filter_map(|l| l.init.map(|init| AigEdge::new(l.input, !init))),
         )
     }
+
+    /// Returns the edges driving latch nexts, outputs, bads, constraints,
+    /// justice and fairness.
+    pub fn roots(&self) -> Vec<AigEdge> {
+        let mut roots: Vec<AigEdge> = self.latchs.iter().map(|l| l.next).collect();
+        roots.extend(self.outputs.iter());
+        roots.extend(self.bads.iter());
+        roots.extend(self.constraints.iter());
+        roots.extend(self.justice.iter().flatten());
+        roots.extend(self.fairness.iter());
+        roots
+    }
+
+    /// Returns the safety properties, the bads, or the outputs if the aig has
+    /// no bads as in aiger 1.0.
+    pub fn properties(&self) -> &[AigEdge] {
+        if self.bads.is_empty() {
+            &self.outputs
+        } else {
+            &self.bads
+        }
+    }
 }
@@ -0,0 +1,471 @@
+use crate::{
+    cleanup::map_edge,
+    cut::{cofactor, CUT_SIZE, VAR_TRUTH},
+    Aig, AigEdge, AigNodeId,
+};
+use std::collections::HashMap;
+
+fn support(truth: u16) -> u8 {
+    (0..CUT_SIZE)
+        .filter(|v| cofactor(truth, *v, false) != cofactor(truth, *v, true))
+        .fold(0, |s, v| s | 1 << v)
+}
+
+fn vars(set: u8) -> impl Iterator<Item = usize> {
+    (0..CUT_SIZE).filter(move |v| set >> v & 1 == 1)
+}
+
+fn exists(truth: u16, set: u8) -> u16 {
+    vars(set).fold(truth, |t, v| cofactor(t, v, false) | cofactor(t, v, true))
+}
+
+fn cofactor_false(truth: u16, set: u8) -> u16 {
+    vars(set).fold(truth, |t, v| cofactor(t, v, false))
+}
+
+/// Truth tables are normalized to be false on the all-false minterm, the
+/// complement is free on an edge.
+fn normalize(truth: u16) -> (u16, bool) {
+    if truth & 1 == 1 {
+        (!truth, true)
+    } else {
+        (truth, false)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Structure {
+    Const,
+    Var(usize),
+    /// And of two functions, complemented if the flag is set.
+    And(u16, u16, bool),
+    /// Xor of two functions, as the or of two and nodes.
+    Xor(u16, u16),
+    /// Shannon expansion on a variable, as the or of the and nodes of the
+    /// variable with the positive and of its complement with the negative
+    /// cofactor.
+    Mux(usize, u16, u16),
+}
+
+/// Heuristic and structures of the functions of four variables. The structure
+/// of a normalized truth table is derived once, only from the structures of
+/// functions of fewer variables, by disjoint and, or and xor decompositions
+/// and shannon expansions, keeping the first one with the fewest distinct and
+/// nodes. This is not an exhaustive search, so the structures are small but
+/// not always optimal. They do not depend on the order of derivation.
+#[derive(Default)]
+struct Library {
+    structures: HashMap<u16, (Structure, Vec<u16>)>,
+}
+
+impl Library {
+    /// Returns the and nodes, as normalized truth tables, of the structure of
+    /// `truth`.
+    fn synthesize(&mut self, truth: u16) -> Vec<u16> {
+        let (truth, _) = normalize(truth);
+        if let Some((_, nodes)) = self.structures.get(&truth) {
+            return nodes.clone();
+        }
+        let support = support(truth);
+        let (structure, nodes) = if support == 0 {
+            (Structure::Const, Vec::new())
+        } else if let Some(v) = (0..CUT_SIZE).find(|v| VAR_TRUTH[*v] == truth) {
+            (Structure::Var(v), Vec::new())
+        } else {
+            let mut candidates = Vec::new();
+            let lowest = support & support.wrapping_neg();
+            for part in 1..support {
+                if part & !support != 0 || part & lowest == 0 {
+                    continue;
+                }
+                let other = support & !part;
+                for (t, compl) in [(truth, false), (!truth, true)] {
+                    let g = exists(t, other);
+                    let h = exists(t, part);
+                    if g & h == t {
+                        candidates.push((Structure::And(g, h, compl), vec![truth]));
+                    }
+                }
+                let g = cofactor_false(truth, other);
+                let h = cofactor_false(truth, part);
+                if g ^ h == truth {
+                    let nodes = vec![normalize(g & !h).0, normalize(!g & h).0, truth];
+                    candidates.push((Structure::Xor(g, h), nodes));
+                }
+            }
+            for v in vars(support) {
+                let f0 = cofactor(truth, v, false);
+                let f1 = cofactor(truth, v, true);
+                if [f0, f1].iter().all(|f| *f != 0 && *f != !0) {
+                    let nodes = vec![
+                        normalize(VAR_TRUTH[v] & f1).0,
+                        normalize(!VAR_TRUTH[v] & f0).0,
+                        truth,
+                    ];
+                    candidates.push((Structure::Mux(v, f1, f0), nodes));
+                }
+            }
+            let mut best: Option<(Structure, Vec<u16>)> = None;
+            for (structure, mut nodes) in candidates {
+                let (x, y) = match structure {
+                    Structure::And(x, y, _) | Structure::Xor(x, y) | Structure::Mux(_, x, y) => {
+                        (x, y)
+                    }
+                    Structure::Const | Structure::Var(_) => unreachable!(),
+                };
+                nodes.extend(self.synthesize(x));
+                nodes.extend(self.synthesize(y));
+                nodes.sort();
+                nodes.dedup();
+                if best.as_ref().is_none_or(|(_, n)| nodes.len() < n.len()) {
+                    best = Some((structure, nodes));
+                }
+            }
+            best.unwrap()
+        };
+        self.structures.insert(truth, (structure, nodes.clone()));
+        nodes
+    }
+
+    fn structure(&self, truth: u16) -> (&Structure, bool) {
+        let (truth, compl) = normalize(truth);
+        (&self.structures[&truth].0, compl)
+    }
+
+    /// Counts the and nodes the structure of `truth` over `leaves` would add
+    /// to `aig`, reusing the nodes already hashed.
+    fn count(
+        &self,
+        aig: &Aig,
+        leaves: &[AigEdge],
+        truth: u16,
+        cache: &mut HashMap<u16, Option<AigEdge>>,
+        added: &mut usize,
+    ) -> Option<AigEdge> {
+        let (structure, compl) = self.structure(truth);
+        let key = normalize(truth).0;
+        let edge = match cache.get(&key) {
+            Some(edge) => *edge,
+            None => {
+                let edge = match structure.clone() {
+                    Structure::Const => Some(AigEdge::constant_edge(false)),
+                    Structure::Var(v) => Some(leaves[v]),
+                    Structure::And(x, y, c) => {
+                        let x_edge = self.count(aig, leaves, x, cache, added);
+                        let y_edge = self.count(aig, leaves, y, cache, added);
+                        count_and(aig, x & y, x_edge, y_edge, cache, added).map(|e| e.not_if(c))
+                    }
+                    Structure::Xor(g, h) => {
+                        let g_edge = self.count(aig, leaves, g, cache, added);
+                        let h_edge = self.count(aig, leaves, h, cache, added);
+                        let a = count_and(aig, g & !h, g_edge, h_edge.map(|e| !e), cache, added);
+                        let b = count_and(aig, !g & h, g_edge.map(|e| !e), h_edge, cache, added);
+                        let t = !(g & !h) & !(!g & h);
+                        count_and(aig, t, a.map(|e| !e), b.map(|e| !e), cache, added).map(|e| !e)
+                    }
+                    Structure::Mux(v, f1, f0) => {
+                        let f1_edge = self.count(aig, leaves, f1, cache, added);
+                        let f0_edge = self.count(aig, leaves, f0, cache, added);
+                        let var = VAR_TRUTH[v];
+                        let a = count_and(aig, var & f1, Some(leaves[v]), f1_edge, cache, added);
+                        let b = count_and(aig, !var & f0, Some(!leaves[v]), f0_edge, cache, added);
+                        let t = !(var & f1) & !(!var & f0);
+                        count_and(aig, t, a.map(|e| !e), b.map(|e| !e), cache, added).map(|e| !e)
+                    }
+                };
+                cache.insert(key, edge);
+                edge
+            }
+        };
+        edge.map(|e| e.not_if(compl))
+    }
+
+    /// Builds the structure of `truth` over `leaves` into `aig`.
+    fn build(
+        &self,
+        aig: &mut Aig,
+        leaves: &[AigEdge],
+        truth: u16,
+        cache: &mut HashMap<u16, AigEdge>,
+    ) -> AigEdge {
+        let (structure, compl) = self.structure(truth);
+        let key = normalize(truth).0;
+        let edge = match cache.get(&key) {
+            Some(edge) => *edge,
+            None => {
+                let edge = match structure.clone() {
+                    Structure::Const => AigEdge::constant_edge(false),
+                    Structure::Var(v) => leaves[v],
+                    Structure::And(x, y, c) => {
+                        let x_edge = self.build(aig, leaves, x, cache);
+                        let y_edge = self.build(aig, leaves, y, cache);
+                        build_and(aig, x & y, x_edge, y_edge, cache).not_if(c)
+                    }
+                    Structure::Xor(g, h) => {
+                        let g_edge = self.build(aig, leaves, g, cache);
+                        let h_edge = self.build(aig, leaves, h, cache);
+                        let a = build_and(aig, g & !h, g_edge, !h_edge, cache);
+                        let b = build_and(aig, !g & h, !g_edge, h_edge, cache);
+                        !build_and(aig, !(g & !h) & !(!g & h), !a, !b, cache)
+                    }
+                    Structure::Mux(v, f1, f0) => {
+                        let f1_edge = self.build(aig, leaves, f1, cache);
+                        let f0_edge = self.build(aig, leaves, f0, cache);
+                        let var = VAR_TRUTH[v];
+                        let a = build_and(aig, var & f1, leaves[v], f1_edge, cache);
+                        let b = build_and(aig, !var & f0, !leaves[v], f0_edge, cache);
+                        !build_and(aig, !(var & f1) & !(!var & f0), !a, !b, cache)
+                    }
+                };
+                cache.insert(key, edge);
+                edge
+            }
+        };
+        edge.not_if(compl)
+    }
+}
+
+/// Builds the and node of `x` and `y`, whose function is `truth`, or reuses
+/// the node already built for the function.
+fn build_and(
+    aig: &mut Aig,
+    truth: u16,
+    x: AigEdge,
+    y: AigEdge,
+    cache: &mut HashMap<u16, AigEdge>,
+) -> AigEdge {
+    let (key, compl) = normalize(truth);
+    let edge = *cache
+        .entry(key)
+        .or_insert_with(|| aig.new_and_node(x, y).not_if(compl));
+    edge.not_if(compl)
+}
+
+/// Looks up the and node of `x` and `y`, whose function is `truth`, counting
+/// it as added if it is missing.
+fn count_and(
+    aig: &Aig,
+    truth: u16,
+    x: Option<AigEdge>,
+    y: Option<AigEdge>,
+    cache: &mut HashMap<u16, Option<AigEdge>>,
+    added: &mut usize,
+) -> Option<AigEdge> {
+    let (key, compl) = normalize(truth);
+    if let Some(edge) = cache.get(&key) {
+        return edge.map(|e| e.not_if(compl));
+    }
+    let edge = match (x, y) {
+        (Some(x), Some(y)) => aig.strash_lookup(x, y),
+        _ => None,
+    };
+    if edge.is_none() {
+        *added += 1;
+    }
+    cache.insert(key, edge.map(|e| e.not_if(compl)));
+    edge
+}
+
+impl Aig {
+    /// Number of references of every node: fanouts and roots.
+    pub(crate) fn references(&self) -> Vec<usize> {
+        let mut refs: Vec<usize> = self.nodes.iter().map(|n| n.fanouts.len()).collect();
+        for root in self.roots() {
+            refs[root.node_id()] += 1;
+        }
+        refs
+    }
+
+    /// Dereferences the cone of `id` above `leaves` and returns the size of
+    /// its maximum fanout free cone.
+    fn mffc_deref(&self, refs: &mut [usize], id: AigNodeId, leaves: &[AigNodeId]) -> usize {
+        let mut size = 1;
+        for fanin in [self.nodes[id].fanin0(), self.nodes[id].fanin1()] {
+            let fanin = fanin.node_id();
+            if leaves.contains(&fanin) || !self.nodes[fanin].is_and() {
+                continue;
+            }
+            refs[fanin] -= 1;
+            if refs[fanin] == 0 {
+                size += self.mffc_deref(refs, fanin, leaves);
+            }
+        }
+        size
+    }
+
+    fn mffc_ref(&self, refs: &mut [usize], id: AigNodeId, leaves: &[AigNodeId]) {
+        for fanin in [self.nodes[id].fanin0(), self.nodes[id].fanin1()] {
+            let fanin = fanin.node_id();
+            if leaves.contains(&fanin) || !self.nodes[fanin].is_and() {
+                continue;
+            }
+            if refs[fanin] == 0 {
+                self.mffc_ref(refs, fanin, leaves);
+            }
+            refs[fanin] += 1;
+        }
+    }
+
+    /// And-tree balancing. Every maximal tree of uncomplemented, single
+    /// referenced and nodes is rebuilt with its leaves paired by increasing
+    /// level. Inputs and latches keep their order.
+    pub fn balance(&self) -> Aig {
+        let refs = self.references();
+        let (mut aig, _) = self.reconstruct(|aig, map, id| {
+            let mut leaves = Vec::new();
+            let mut stack = vec![self.nodes[id].fanin0(), self.nodes[id].fanin1()];
+            while let Some(e) = stack.pop() {
+                let node = &self.nodes[e.node_id()];
+                if !e.compl() && node.is_and() && refs[e.node_id()] == 1 {
+                    stack.push(node.fanin0());
+                    stack.push(node.fanin1());
+                } else {
+                    leaves.push(map_edge(map, e));
+                }
+            }
+            aig.new_and_nodes(leaves)
+        });
+        aig.cleanup();
+        aig
+    }
+
+    /// DAG-aware rewriting. Every and node is replaced by the structure of
+    /// the function of one of its 4-feasible cuts if this removes more nodes
+    /// of its maximum fanout free cone than it adds, counting the nodes which
+    /// already exist as free. Inputs and latches keep their order.
+    pub fn rewrite(&self) -> Aig {
+        let cone = self.fanin_logic_cone(&self.roots());
+        let cuts = self.enumerate_cuts(&cone);
+        let mut refs = self.references();
+        let mut library = Library::default();
+        let (mut aig, _) = self.reconstruct(|aig, map, id| {
+            let mut best: Option<(usize, usize)> = None;
+            for (i, cut) in cuts[id].iter().enumerate() {
+                if cut.leaves == [id] {
+                    continue;
+                }
+                library.synthesize(cut.truth);
+                let leaves: Vec<AigEdge> = cut.leaves.iter().map(|l| map[*l].unwrap()).collect();
+                let mut added = 0;
+                library.count(aig, &leaves, cut.truth, &mut HashMap::new(), &mut added);
+                let removed = self.mffc_deref(&mut refs, id, &cut.leaves);
+                self.mffc_ref(&mut refs, id, &cut.leaves);
+                if removed > added && best.is_none_or(|(_, gain)| removed - added > gain) {
+                    best = Some((i, removed - added));
+                }
+            }
+            match best {
+                Some((i, _)) => {
+                    let cut = &cuts[id][i];
+                    let leaves: Vec<AigEdge> =
+                        cut.leaves.iter().map(|l| map[*l].unwrap()).collect();
+                    library.build(aig, &leaves, cut.truth, &mut HashMap::new())
+                }
+                None => self.copy_and(aig, map, id),
+            }
+        });
+        aig.cleanup();
+        let num_ands = cone
+            .iter()
+            .enumerate()
+            .filter(|(id, c)| **c && self.nodes[*id].is_and())
+            .count();
+        if aig.ands_iter().count() < num_ands {
+            aig
+        } else {
+            let mut aig = self.clone();
+            aig.cleanup();
+            aig
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{aig, truth, truths};
+
+    #[test]
+    fn library_sizes() {
+        let [a, b, c, d] = VAR_TRUTH;
+        let mut library = Library::default();
+        assert_eq!(library.synthesize(a & b & c & d).len(), 3);
+        assert_eq!(library.synthesize(a ^ b).len(), 3);
+        assert_eq!(library.synthesize((a & b) | (!a & c)).len(), 3);
+        assert_eq!(library.synthesize((a ^ b) & (c | d)).len(), 5);
+    }
+
+    #[test]
+    fn library_is_independent_of_order() {
+        let truths: Vec<u16> = (0..=u16::MAX).step_by(97).collect();
+        let mut forward = Library::default();
+        let mut backward = Library::default();
+        let sizes: Vec<usize> = truths
+            .iter()
+            .map(|t| forward.synthesize(*t).len())
+            .collect();
+        let mut reversed: Vec<usize> = truths
+            .iter()
+            .rev()
+            .map(|t| backward.synthesize(*t).len())
+            .collect();
+        reversed.reverse();
+        assert_eq!(sizes, reversed);
+    }
+
+    #[test]
+    fn library_builds_its_functions() {
+        let mut library = Library::default();
+        for function in (0..=u16::MAX).step_by(89) {
+            let mut aig = Aig::new();
+            let leaves: Vec<AigEdge> = (0..CUT_SIZE).map(|_| aig.new_input_node().into()).collect();
+            library.synthesize(function);
+            let mut added = 0;
+            library.count(&aig, &leaves, function, &mut HashMap::new(), &mut added);
+            let output = library.build(&mut aig, &leaves, function, &mut HashMap::new());
+            assert_eq!(truth(&aig, output) as u16, function);
+            assert_eq!(aig.ands_iter().count(), added);
+        }
+    }
+
+    #[test]
+    fn rewrite_removes_redundant_nodes() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 6 2 1 1 3\n",
+            "2\n",
+            "4\n",
+            "6 13\n",
+            "13\n",
+            "8 2 4\n",
+            "10 2 5\n",
+            "12 9 11\n",
+        ));
+        let rewritten = aig.rewrite();
+
+        assert_eq!(truths(&rewritten), truths(&aig));
+        assert_eq!(rewritten.ands_iter().count(), 0);
+        assert_eq!(rewritten.outputs[0].node_id(), rewritten.inputs[0]);
+    }
+
+    #[test]
+    fn balance_reduces_depth() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 7 4 0 1 3\n",
+            "2\n",
+            "4\n",
+            "6\n",
+            "8\n",
+            "14\n",
+            "10 2 4\n",
+            "12 10 6\n",
+            "14 12 8\n",
+        ));
+        let balanced = aig.balance();
+
+        assert_eq!(truths(&balanced), truths(&aig));
+        assert_eq!(balanced.ands_iter().count(), 3);
+        assert_eq!(balanced.nodes[balanced.outputs[0].node_id()].level, 2);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::{Aig, AigEdge, AigNodeId};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn edge_value(values: &[u64], e: AigEdge) -> u64 {
+    if e.compl() {
+        !values[e.node_id()]
+    } else {
+        values[e.node_id()]
+    }
+}
+
+impl Aig {
+    /// Simulates 64 patterns at once through the combinational logic, where
+    /// `leaf` gives the values of the inputs, latches and constant. Returns
+    /// the values of all nodes.
+    pub fn simulate_word<F: FnMut(AigNodeId) -> u64>(&self, mut leaf: F) -> Vec<u64> {
+        let mut values = vec![0; self.num_nodes()];
+        for id in self.nodes_range() {
+            values[id] = if self.nodes[id].is_and() {
+                edge_value(&values, self.nodes[id].fanin0())
+                    & edge_value(&values, self.nodes[id].fanin1())
+            } else {
+                leaf(id)
+            };
+        }
+        values
+    }
+}
+
+/// A concrete trace of an aig: the initial values of the latches and the
+/// values of the inputs in every cycle, by position.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AigTrace {
+    pub init: Vec<bool>,
+    pub inputs: Vec<Vec<bool>>,
+}
+
+/// Bit-parallel sequential simulator, which runs 64 traces at once from the
+/// initial states. Uninitialized latches start at random values.
+pub struct AigSimulator<'a> {
+    aig: &'a Aig,
+    rng: StdRng,
+    state: Vec<u64>,
+    values: Vec<u64>,
+}
+
+impl<'a> AigSimulator<'a> {
+    pub fn new(aig: &'a Aig, seed: u64) -> Self {
+        let mut simulator = Self {
+            aig,
+            rng: StdRng::seed_from_u64(seed),
+            state: Vec::new(),
+            values: vec![0; aig.num_nodes()],
+        };
+        simulator.reset();
+        simulator
+    }
+
+    /// Restarts the traces from the initial states.
+    pub fn reset(&mut self) {
+        self.state = self
+            .aig
+            .latchs
+            .iter()
+            .map(|l| match l.init {
+                Some(true) => !0,
+                Some(false) => 0,
+                None => self.rng.gen(),
+            })
+            .collect();
+    }
+
+    /// Sets the values of the latches, by position, in the current cycle.
+    pub fn set_state(&mut self, state: &[u64]) {
+        assert_eq!(state.len(), self.aig.latchs.len());
+        self.state = state.to_vec();
+    }
+
+    /// Values of the latches, by position, in the current cycle.
+    pub fn state(&self) -> &[u64] {
+        &self.state
+    }
+
+    /// Values of all nodes in the last simulated cycle.
+    pub fn values(&self) -> &[u64] {
+        &self.values
+    }
+
+    pub fn edge_value(&self, e: AigEdge) -> u64 {
+        edge_value(&self.values, e)
+    }
+
+    /// Simulates one cycle with the given values of the inputs, by position,
+    /// and moves the latches to their nexts.
+    pub fn step(&mut self, inputs: &[u64]) {
+        assert_eq!(inputs.len(), self.aig.inputs.len());
+        for (input, value) in self.aig.inputs.iter().zip(inputs.iter()) {
+            self.values[*input] = *value;
+        }
+        for (l, value) in self.aig.latchs.iter().zip(self.state.iter()) {
+            self.values[l.input] = *value;
+        }
+        for id in self.aig.nodes_range() {
+            if self.aig.nodes[id].is_and() {
+                self.values[id] = edge_value(&self.values, self.aig.nodes[id].fanin0())
+                    & edge_value(&self.values, self.aig.nodes[id].fanin1());
+            }
+        }
+        for (l, value) in self.aig.latchs.iter().zip(self.state.iter_mut()) {
+            *value = edge_value(&self.values, l.next);
+        }
+    }
+
+    /// Simulates one cycle with random inputs and returns them.
+    pub fn step_random(&mut self) -> Vec<u64> {
+        let inputs: Vec<u64> = self.aig.inputs.iter().map(|_| self.rng.gen()).collect();
+        self.step(&inputs);
+        inputs
+    }
+
+    /// Simulates `cycles` cycles with random inputs and returns the
+    /// signature of every node, its values in each cycle.
+    pub fn signatures(&mut self, cycles: usize) -> Vec<Vec<u64>> {
+        let mut signatures = vec![Vec::with_capacity(cycles); self.aig.num_nodes()];
+        for _ in 0..cycles {
+            self.step_random();
+            for (signature, value) in signatures.iter_mut().zip(self.values.iter()) {
+                signature.push(*value);
+            }
+        }
+        signatures
+    }
+}
+
+impl Aig {
+    /// Random simulation of 64 traces from the initial states for at most
+    /// `cycles` cycles. Returns a trace which reaches `bad` while the
+    /// constraints hold in every cycle, ending in the cycle `bad` holds.
+    pub fn random_simulate_bad(&self, bad: AigEdge, cycles: usize, seed: u64) -> Option<AigTrace> {
+        let mut simulator = AigSimulator::new(self, seed);
+        let init = simulator.state().to_vec();
+        let mut inputs = Vec::new();
+        let mut valid = !0;
+        for _ in 0..cycles {
+            inputs.push(simulator.step_random());
+            for c in self.constraints.iter() {
+                valid &= simulator.edge_value(*c);
+            }
+            let hit = simulator.edge_value(bad) & valid;
+            if hit != 0 {
+                let bit = hit.trailing_zeros();
+                let value = |w: &u64| w >> bit & 1 == 1;
+                return Some(AigTrace {
+                    init: init.iter().map(value).collect(),
+                    inputs: inputs
+                        .iter()
+                        .map(|i: &Vec<u64>| i.iter().map(value).collect())
+                        .collect(),
+                });
+            }
+            if valid == 0 {
+                break;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AigSimulator;
+    use crate::tests::aig;
+
+    #[test]
+    fn simulator_matches_simulate_word() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 5 2 1 1 2\n",
+            "2\n",
+            "4\n",
+            "6 10 1\n",
+            "11\n",
+            "8 2 6\n",
+            "10 9 4\n",
+        ));
+        let mut simulator = AigSimulator::new(&aig, 0);
+        assert_eq!(simulator.state(), &[!0]);
+        for _ in 0..4 {
+            let state = simulator.state().to_vec();
+            let inputs = simulator.step_random();
+            let values = aig.simulate_word(|id| match id {
+                1 => inputs[0],
+                2 => inputs[1],
+                3 => state[0],
+                _ => 0,
+            });
+            assert_eq!(simulator.values(), &values);
+            assert_eq!(simulator.state(), &[values[5]]);
+        }
+        simulator.reset();
+        assert_eq!(simulator.state(), &[!0]);
+    }
+
+    #[test]
+    fn random_simulate_reaches_bad() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 3 1 2 0 0 1\n",
+            "2\n",
+            "4 2\n",
+            "6 4\n",
+            "6\n",
+        ));
+        let trace = aig.random_simulate_bad(aig.bads[0], 10, 0).unwrap();
+        assert_eq!(trace.init, vec![false, false]);
+        assert_eq!(trace.inputs.len(), 3);
+        assert!(trace.inputs[0][0]);
+    }
+
+    #[test]
+    fn random_simulate_respects_constraints() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 3 1 2 0 0 1 1\n",
+            "2\n",
+            "4 2\n",
+            "6 4\n",
+            "6\n",
+            "3\n",
+        ));
+        assert_eq!(aig.random_simulate_bad(aig.bads[0], 10, 0), None);
+    }
+}
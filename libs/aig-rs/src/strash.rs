@@ -0,0 +1,82 @@
+use crate::{Aig, AigEdge, AigNodeId};
+use std::collections::HashMap;
+
+/// Structural hash table, mapping the (sorted) fanins of every and node to the
+/// node.
+pub(crate) type Strash = HashMap<(AigEdge, AigEdge), AigNodeId>;
+
+/// Folds the and of two fanins sorted by node id into an existing edge if the
+/// and is trivial, i.e. one fanin is constant or both fanins are on the same
+/// node.
+pub(crate) fn and_simplify(fanin0: AigEdge, fanin1: AigEdge) -> Option<AigEdge> {
+    debug_assert!(fanin0.node_id() <= fanin1.node_id());
+    if fanin0 == AigEdge::constant_edge(true) {
+        Some(fanin1)
+    } else if fanin0 == AigEdge::constant_edge(false) || fanin0 == !fanin1 {
+        Some(AigEdge::constant_edge(false))
+    } else if fanin0 == fanin1 {
+        Some(fanin0)
+    } else {
+        None
+    }
+}
+
+impl Aig {
+    /// Builds the structural hash table from scratch. Structurally identical
+    /// and nodes are kept, the one with the smallest id is hashed.
+    pub(crate) fn setup_strash(&mut self) {
+        self.strash.clear();
+        for id in self.nodes_range() {
+            if self.nodes[id].is_and() {
+                let fanin0 = self.nodes[id].fanin0();
+                let fanin1 = self.nodes[id].fanin1();
+                if and_simplify(fanin0, fanin1).is_none() {
+                    self.strash.entry((fanin0, fanin1)).or_insert(id);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn strash_remove(&mut self, id: AigNodeId) {
+        if self.nodes[id].is_and() {
+            let key = (self.nodes[id].fanin0(), self.nodes[id].fanin1());
+            if self.strash.get(&key) == Some(&id) {
+                self.strash.remove(&key);
+            }
+        }
+    }
+
+    /// Returns the edge an and node is equivalent to if it is trivial or
+    /// structurally identical to another node, and hashes it otherwise.
+    pub(crate) fn strash_insert(&mut self, id: AigNodeId) -> Option<AigEdge> {
+        let fanin0 = self.nodes[id].fanin0();
+        let fanin1 = self.nodes[id].fanin1();
+        if let Some(edge) = and_simplify(fanin0, fanin1) {
+            return Some(edge);
+        }
+        match self.strash.get(&(fanin0, fanin1)) {
+            Some(other) if *other != id => Some((*other).into()),
+            _ => {
+                self.strash.insert((fanin0, fanin1), id);
+                None
+            }
+        }
+    }
+
+    /// Returns the edge an and of two fanins would be folded or hashed to,
+    /// without creating a node.
+    pub(crate) fn strash_lookup(
+        &self,
+        mut fanin0: AigEdge,
+        mut fanin1: AigEdge,
+    ) -> Option<AigEdge> {
+        if fanin0.node_id() > fanin1.node_id() {
+            std::mem::swap(&mut fanin0, &mut fanin1);
+        }
+        and_simplify(fanin0, fanin1).or_else(|| {
+            self.strash
+                .get(&(fanin0, fanin1))
+                .map(|id| AigEdge::from(*id))
+        })
+    }
+}
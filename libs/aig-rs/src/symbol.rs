@@ -0,0 +1,31 @@
+use crate::{Aig, AigNodeId};
+use std::collections::HashMap;
+
+/// Names from the aiger symbol table. Inputs and latches are named by their
+/// node id, outputs, bads, constraints, justice and fairness by their
+/// position.
+#[derive(Debug, Clone, Default)]
+pub struct AigSymbols {
+    pub nodes: HashMap<AigNodeId, String>,
+    pub outputs: HashMap<usize, String>,
+    pub bads: HashMap<usize, String>,
+    pub constraints: HashMap<usize, String>,
+    pub justice: HashMap<usize, String>,
+    pub fairness: HashMap<usize, String>,
+}
+
+impl Aig {
+    pub fn node_name(&self, id: AigNodeId) -> Option<&str> {
+        self.symbols.nodes.get(&id).map(|s| s.as_str())
+    }
+
+    /// Returns the name of property `i` of [`Aig::properties`].
+    pub fn property_name(&self, i: usize) -> Option<&str> {
+        let names = if self.bads.is_empty() {
+            &self.symbols.outputs
+        } else {
+            &self.symbols.bads
+        };
+        names.get(&i).map(|s| s.as_str())
+    }
+}
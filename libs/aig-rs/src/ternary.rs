@@ -1,6 +1,6 @@
-use crate::{Aig, AigNodeId};
+use crate::{Aig, AigEdge, AigNodeId};
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
     ops::{BitAnd, BitOr, Not},
 };
 
@@ -141,3 +141,93 @@ impl Aig {
         simulation
     }
 }
+
+impl Aig {
+    /// Ternary simulation from the initial state, with inputs and
+    /// uninitialized latches as X, until the set of simulated states reaches a
+    /// fixed point. Returns the input nodes of the latches which are constant
+    /// in every reachable state, with their value.
+    pub fn ternary_constant_latchs(&self) -> Vec<(AigNodeId, bool)> {
+        let primary_inputs = vec![TernaryValue::X; self.inputs.len()];
+        let mut state: Vec<TernaryValue> = self
+            .latchs
+            .iter()
+            .map(|l| l.init.map_or(TernaryValue::X, TernaryValue::from))
+            .collect();
+        loop {
+            let simulation = self.ternary_simulate(&primary_inputs, &state);
+            let mut changed = false;
+            for (value, latch) in state.iter_mut().zip(self.latchs.iter()) {
+                let next = simulation[latch.next.node_id()].not_if(latch.next.compl());
+                if *value != TernaryValue::X && *value != next {
+                    *value = TernaryValue::X;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        self.latchs
+            .iter()
+            .zip(state)
+            .filter_map(|(l, v)| match v {
+                TernaryValue::True => Some((l.input, true)),
+                TernaryValue::False => Some((l.input, false)),
+                TernaryValue::X => None,
+            })
+            .collect()
+    }
+
+    /// Replaces the latches found by `ternary_constant_latchs` by constants
+    /// and removes them. Returns the number of removed latches.
+    pub fn remove_constant_latchs(&mut self) -> usize {
+        let constants = self.ternary_constant_latchs();
+        for (input, value) in constants.iter() {
+            self.merge_fe_node((*input).into(), AigEdge::constant_edge(*value));
+        }
+        let constants: HashSet<AigNodeId> = constants.into_iter().map(|(i, _)| i).collect();
+        self.latchs.retain(|l| !constants.contains(&l.input));
+        constants.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::aig;
+    use crate::AigEdge;
+
+    #[test]
+    fn constant_latchs_stuck_uninitialized_toggling() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 5 1 3 0 1\n",
+            "2\n",
+            "4 8\n",
+            "6 6 6\n",
+            "10 11\n",
+            "8 4 2\n",
+        ));
+        assert_eq!(aig.ternary_constant_latchs(), vec![(2, false)]);
+    }
+
+    #[test]
+    fn constant_latchs_fixed_point() {
+        #[rustfmt::skip]
+        let mut aig = aig(concat!(
+            "aag 6 1 4 1 1\n",
+            "2\n",
+            "4 12\n",
+            "6 5 1\n",
+            "8 2\n",
+            "10 8\n",
+            "6\n",
+            "12 4 2\n",
+        ));
+        assert_eq!(aig.ternary_constant_latchs(), vec![(2, false), (3, true)]);
+        assert_eq!(aig.remove_constant_latchs(), 2);
+        let inputs: Vec<_> = aig.latchs.iter().map(|l| l.input).collect();
+        assert_eq!(inputs, vec![4, 5]);
+        assert_eq!(aig.outputs, vec![AigEdge::constant_edge(true)]);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{cleanup::map_edge, Aig, AigEdge, AigNodeId};
+
+/// A time frame expansion of an aig. The maps are indexed by frame and then
+/// by the position of the input, latch, output or bad in the original aig.
+#[derive(Debug, Clone)]
+pub struct AigUnrolling {
+    /// The combinational aig. Its outputs, bads and constraints are those of
+    /// every frame, frame by frame.
+    pub aig: Aig,
+    pub inputs: Vec<Vec<AigNodeId>>,
+    pub latchs: Vec<Vec<AigEdge>>,
+    pub outputs: Vec<Vec<AigEdge>>,
+    pub bads: Vec<Vec<AigEdge>>,
+}
+
+impl Aig {
+    /// Expands the aig into `k` time frames. Latches are their init values in
+    /// frame 0, or fresh inputs if uninitialized, and the nexts of frame `i`
+    /// in frame `i + 1`. Justice and fairness are dropped.
+    pub fn unroll(&self, k: usize) -> AigUnrolling {
+        let cone = self.fanin_logic_cone(&self.roots());
+        let mut aig = Aig::new();
+        let mut unrolling = AigUnrolling {
+            aig: Aig::new(),
+            inputs: Vec::with_capacity(k),
+            latchs: Vec::with_capacity(k),
+            outputs: Vec::with_capacity(k),
+            bads: Vec::with_capacity(k),
+        };
+        let mut latchs: Vec<AigEdge> = Vec::new();
+        for frame in 0..k {
+            let inputs: Vec<AigNodeId> = self.inputs.iter().map(|_| aig.new_input_node()).collect();
+            if frame == 0 {
+                latchs = self
+                    .latchs
+                    .iter()
+                    .map(|l| match l.init {
+                        Some(init) => AigEdge::constant_edge(init),
+                        None => aig.new_input_node().into(),
+                    })
+                    .collect();
+            }
+            let mut map = vec![None; self.num_nodes()];
+            for (input, node) in self.inputs.iter().zip(inputs.iter()) {
+                map[*input] = Some((*node).into());
+            }
+            for (l, edge) in self.latchs.iter().zip(latchs.iter()) {
+                map[l.input] = Some(*edge);
+            }
+            self.copy_cone(&mut aig, &cone, &mut map);
+            for (input, node) in self.inputs.iter().zip(inputs.iter()) {
+                if let Some(name) = self.node_name(*input) {
+                    aig.symbols.nodes.insert(*node, format!("{name}@{frame}"));
+                }
+            }
+            let map_edges = |edges: &[AigEdge]| -> Vec<AigEdge> {
+                edges.iter().map(|e| map_edge(&map, *e)).collect()
+            };
+            let outputs = map_edges(&self.outputs);
+            let bads = map_edges(&self.bads);
+            aig.outputs.extend(outputs.iter());
+            aig.bads.extend(bads.iter());
+            aig.constraints.extend(map_edges(&self.constraints));
+            unrolling.inputs.push(inputs);
+            unrolling.latchs.push(latchs);
+            unrolling.outputs.push(outputs);
+            unrolling.bads.push(bads);
+            latchs = self.latchs.iter().map(|l| map_edge(&map, l.next)).collect();
+        }
+        unrolling.aig = aig;
+        unrolling
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::aig;
+    use crate::{AigEdge, AigSimulator};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    #[test]
+    fn unrolling_agrees_with_simulation() {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 11 1 2 1 8 1\n",
+            "2\n",
+            "4 13 0\n",
+            "6 21 6\n",
+            "22\n",
+            "6\n",
+            "8 4 3\n",
+            "10 5 2\n",
+            "12 9 11\n",
+            "14 4 2\n",
+            "16 6 15\n",
+            "18 7 14\n",
+            "20 17 19\n",
+            "22 4 6\n",
+        ));
+        let k = 5;
+        let unrolling = aig.unroll(k);
+        assert!(unrolling.aig.latchs.is_empty());
+        assert_eq!(unrolling.aig.inputs.len(), k + 1);
+        assert_eq!(unrolling.aig.outputs.len(), k);
+        assert_eq!(unrolling.aig.bads.len(), k);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let leafs: HashMap<usize, u64> = unrolling
+            .aig
+            .inputs
+            .iter()
+            .map(|i| (*i, rng.gen()))
+            .collect();
+        let values = unrolling.aig.simulate_word(|id| leafs[&id]);
+        let value = |e: AigEdge| values[e.node_id()] ^ if e.compl() { !0 } else { 0 };
+        let mut simulator = AigSimulator::new(&aig, 0);
+        simulator.set_state(&[0, value(unrolling.latchs[0][1])]);
+        for frame in 0..k {
+            let inputs: Vec<u64> = unrolling.inputs[frame].iter().map(|i| leafs[i]).collect();
+            simulator.step(&inputs);
+            assert_eq!(
+                simulator.edge_value(aig.outputs[0]),
+                value(unrolling.outputs[frame][0])
+            );
+            assert_eq!(
+                simulator.edge_value(aig.bads[0]),
+                value(unrolling.bads[frame][0])
+            );
+        }
+    }
+}
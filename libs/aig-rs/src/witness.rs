@@ -0,0 +1,345 @@
+use crate::{Aig, AigNodeId, AigSimulator, AigTrace};
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+/// A witness in the HWMCC format, a `1` line, the bads it claims to reach,
+/// the initial latch values and the inputs of every cycle, or a stimulus
+/// file which only has the inputs of every cycle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AigWitness {
+    /// Positions of the claimed bads, the outputs if the aig has no bads.
+    /// Empty for a stimulus file.
+    pub bads: Vec<usize>,
+    pub trace: AigTrace,
+}
+
+/// An error which occurs when reading a witness, with the line it occurs in.
+#[derive(Debug)]
+pub enum WitnessError {
+    Io(io::Error),
+    /// A line is neither a property nor a vector of `0`, `1` and `x`.
+    Syntax(usize),
+    /// A vector has the wrong number of values, the expected and found ones.
+    Width(usize, usize, usize),
+    /// A claimed property is no bad of the aig or a justice property.
+    Property(usize, String),
+}
+
+impl fmt::Display for WitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WitnessError::Io(e) => write!(f, "{e}"),
+            WitnessError::Syntax(line) => write!(f, "line {line}: invalid syntax"),
+            WitnessError::Width(line, x, y) => {
+                write!(f, "line {line}: expected {x} values, found {y}")
+            }
+            WitnessError::Property(line, p) => write!(f, "line {line}: invalid property `{p}`"),
+        }
+    }
+}
+
+impl std::error::Error for WitnessError {}
+
+impl From<io::Error> for WitnessError {
+    fn from(e: io::Error) -> Self {
+        WitnessError::Io(e)
+    }
+}
+
+fn parse_vector(line: usize, s: &str, width: usize) -> Result<Vec<bool>, WitnessError> {
+    let vector = s
+        .chars()
+        .map(|c| match c {
+            '0' | 'x' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(WitnessError::Syntax(line)),
+        })
+        .collect::<Result<Vec<bool>, WitnessError>>()?;
+    if vector.len() != width {
+        return Err(WitnessError::Width(line, width, vector.len()));
+    }
+    Ok(vector)
+}
+
+/// Maps the aiger variables of the nodes to their positions.
+fn var_positions<I: Iterator<Item = AigNodeId>>(aig: &Aig, nodes: I) -> HashMap<usize, usize> {
+    nodes
+        .enumerate()
+        .filter_map(|(i, id)| aig.var_map.origin(id).map(|var| (var, i)))
+        .collect()
+}
+
+fn bits(values: &[bool]) -> String {
+    values.iter().map(|v| if *v { '1' } else { '0' }).collect()
+}
+
+impl AigWitness {
+    /// Parses a witness or a stimulus file of `aig`. Unknown values `x` are
+    /// read as `0`, and a stimulus file starts from the initial values of
+    /// the latches. Only the first witness of a file with several is read,
+    /// up to its `.` line, and it must be a `1` result.
+    pub fn parse(s: &str, aig: &Aig) -> Result<Self, WitnessError> {
+        let lines: Vec<(usize, &str)> = s
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.trim()))
+            .collect();
+        let mut witness = AigWitness::default();
+        let lines = match lines.as_slice() {
+            // a witness of a safe or unknown result has no trace
+            [(line, result), (_, properties), ..]
+                if *result != "1" && properties.starts_with(['b', 'j']) =>
+            {
+                return Err(WitnessError::Syntax(*line));
+            }
+            [(_, "1"), (line, properties), ..] if properties.starts_with('j') => {
+                // justice witnesses need a loop, which is not supported
+                let p = properties.split_whitespace().next().unwrap();
+                return Err(WitnessError::Property(*line, p.to_string()));
+            }
+            [(_, "1"), (line, properties), rest @ ..] if properties.starts_with('b') => {
+                let num_bads = aig.properties().len();
+                for p in properties.split_whitespace() {
+                    match p.strip_prefix('b').and_then(|b| b.parse().ok()) {
+                        Some(b) if b < num_bads => witness.bads.push(b),
+                        _ => return Err(WitnessError::Property(*line, p.to_string())),
+                    }
+                }
+                let (line, init) = rest.first().ok_or(WitnessError::Syntax(*line))?;
+                witness.trace.init = parse_vector(*line, init, aig.latchs.len())?;
+                &rest[1..]
+            }
+            lines => {
+                witness.trace.init = aig.latchs.iter().map(|l| l.init == Some(true)).collect();
+                lines
+            }
+        };
+        for (line, l) in lines.iter() {
+            if *l == "." {
+                break;
+            }
+            if l.is_empty() && !aig.inputs.is_empty() {
+                continue;
+            }
+            witness
+                .trace
+                .inputs
+                .push(parse_vector(*line, l, aig.inputs.len())?);
+        }
+        Ok(witness)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(file: P, aig: &Aig) -> Result<Self, WitnessError> {
+        Self::parse(&fs::read_to_string(file)?, aig)
+    }
+}
+
+impl fmt::Display for AigWitness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "1")?;
+        let bads: Vec<String> = self.bads.iter().map(|b| format!("b{b}")).collect();
+        writeln!(f, "{}", bads.join(" "))?;
+        writeln!(f, "{}", bits(&self.trace.init))?;
+        for inputs in self.trace.inputs.iter() {
+            writeln!(f, "{}", bits(inputs))?;
+        }
+        writeln!(f, ".")
+    }
+}
+
+/// The values of an aig in one cycle of a concrete trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AigTraceStep {
+    pub latchs: Vec<bool>,
+    pub outputs: Vec<bool>,
+    pub bads: Vec<bool>,
+    /// Whether all constraints hold.
+    pub constraints: bool,
+}
+
+impl Aig {
+    /// Simulates a concrete trace and returns the values of every cycle.
+    pub fn simulate_trace(&self, trace: &AigTrace) -> Vec<AigTraceStep> {
+        let word = |v: &bool| if *v { !0 } else { 0 };
+        let mut simulator = AigSimulator::new(self, 0);
+        simulator.set_state(&trace.init.iter().map(word).collect::<Vec<u64>>());
+        let mut steps = Vec::with_capacity(trace.inputs.len());
+        for inputs in trace.inputs.iter() {
+            let latchs = simulator.state().iter().map(|v| v & 1 == 1).collect();
+            simulator.step(&inputs.iter().map(word).collect::<Vec<u64>>());
+            let value = |e| simulator.edge_value(e) & 1 == 1;
+            steps.push(AigTraceStep {
+                latchs,
+                outputs: self.outputs.iter().map(|e| value(*e)).collect(),
+                bads: self.bads.iter().map(|e| value(*e)).collect(),
+                constraints: self.constraints.iter().all(|e| value(*e)),
+            });
+        }
+        steps
+    }
+
+    /// Checks a witness by simulation: the initial values agree with the
+    /// initialized latches, the constraints hold in every cycle and the
+    /// claimed bads, or any bad for a stimulus file, hold in the last cycle.
+    /// Outputs are the bads if the aig has no bads.
+    pub fn check_witness(&self, witness: &AigWitness) -> bool {
+        let trace = &witness.trace;
+        if self
+            .latchs
+            .iter()
+            .zip(trace.init.iter())
+            .any(|(l, v)| l.init.is_some_and(|i| i != *v))
+        {
+            return false;
+        }
+        let steps = self.simulate_trace(trace);
+        if !steps.iter().all(|s| s.constraints) {
+            return false;
+        }
+        let Some(last) = steps.last() else {
+            return false;
+        };
+        let bads = if self.bads.is_empty() {
+            &last.outputs
+        } else {
+            &last.bads
+        };
+        if witness.bads.is_empty() {
+            bads.iter().any(|b| *b)
+        } else {
+            witness.bads.iter().all(|b| bads[*b])
+        }
+    }
+}
+
+impl Aig {
+    /// Translates a trace of the aig to `origin`, the aig the aiger file it
+    /// was derived from loads into, through their var maps. Inputs and
+    /// uninitialized latches without a counterpart are 0, initialized
+    /// latches start at their init values.
+    pub fn map_trace(&self, trace: &AigTrace, origin: &Aig) -> AigTrace {
+        let input_positions = var_positions(origin, origin.inputs.iter().copied());
+        let latch_positions = var_positions(origin, origin.latchs.iter().map(|l| l.input));
+        let mut init: Vec<bool> = origin.latchs.iter().map(|l| l.init == Some(true)).collect();
+        for (l, v) in self.latchs.iter().zip(trace.init.iter()) {
+            if let Some(i) = self
+                .var_map
+                .origin(l.input)
+                .and_then(|var| latch_positions.get(&var))
+            {
+                if origin.latchs[*i].init.is_none() {
+                    init[*i] = *v;
+                }
+            }
+        }
+        let inputs = trace
+            .inputs
+            .iter()
+            .map(|values| {
+                let mut inputs = vec![false; origin.inputs.len()];
+                for (id, v) in self.inputs.iter().zip(values.iter()) {
+                    if let Some(i) = self
+                        .var_map
+                        .origin(*id)
+                        .and_then(|var| input_positions.get(&var))
+                    {
+                        inputs[*i] = *v;
+                    }
+                }
+                inputs
+            })
+            .collect();
+        AigTrace { init, inputs }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AigWitness, WitnessError};
+    use crate::{tests::aig, Aig};
+
+    fn shift() -> Aig {
+        #[rustfmt::skip]
+        let aig = aig(concat!(
+            "aag 3 1 2 0 0 1\n",
+            "2\n",
+            "4 2\n",
+            "6 4\n",
+            "6\n",
+        ));
+        aig
+    }
+
+    #[test]
+    fn witness_parse_and_display() {
+        let aig = shift();
+        let s = "1\nb0\n00\n1\nx\n0\n.\n";
+        let witness = AigWitness::parse(s, &aig).unwrap();
+        assert_eq!(witness.bads, vec![0]);
+        assert_eq!(witness.trace.init, vec![false, false]);
+        assert_eq!(
+            witness.trace.inputs,
+            vec![vec![true], vec![false], vec![false]]
+        );
+        assert!(aig.check_witness(&witness));
+        assert_eq!(witness.to_string(), "1\nb0\n00\n1\n0\n0\n.\n");
+        assert_eq!(
+            AigWitness::parse(&witness.to_string(), &aig).unwrap(),
+            witness
+        );
+    }
+
+    #[test]
+    fn witness_parse_stimulus() {
+        let aig = shift();
+        let witness = AigWitness::parse("1\n0\n0\n", &aig).unwrap();
+        assert!(witness.bads.is_empty());
+        assert_eq!(witness.trace.init, vec![false, false]);
+        assert_eq!(witness.trace.inputs.len(), 3);
+        assert!(aig.check_witness(&witness));
+        let witness = AigWitness::parse("0\n1\n0\n", &aig).unwrap();
+        assert!(!aig.check_witness(&witness));
+    }
+
+    #[test]
+    fn witness_parse_errors() {
+        let aig = shift();
+        assert!(matches!(
+            AigWitness::parse("1\nb1\n00\n1\n.\n", &aig),
+            Err(WitnessError::Property(2, p)) if p == "b1"
+        ));
+        assert!(matches!(
+            AigWitness::parse("1\nj0\n00\n1\n.\n", &aig),
+            Err(WitnessError::Property(2, p)) if p == "j0"
+        ));
+        assert!(matches!(
+            AigWitness::parse("1\nb0\n0\n1\n.\n", &aig),
+            Err(WitnessError::Width(3, 2, 1))
+        ));
+        assert!(matches!(
+            AigWitness::parse("1\nb0\n00\n2\n.\n", &aig),
+            Err(WitnessError::Syntax(4))
+        ));
+    }
+
+    #[test]
+    fn witness_parse_results() {
+        let aig = shift();
+        for s in ["0\nb0\n.\n", "2\nb0\n.\n", "0\nj0\n.\n"] {
+            assert!(matches!(
+                AigWitness::parse(s, &aig),
+                Err(WitnessError::Syntax(1))
+            ));
+        }
+        let s = "1\nb0\n00\n1\n0\n0\n.\n0\nb0\n.\n1\nb0\n00\n1\n.\n";
+        let witness = AigWitness::parse(s, &aig).unwrap();
+        assert_eq!(witness.trace.inputs.len(), 3);
+        assert!(aig.check_witness(&witness));
+    }
+
+    #[test]
+    fn check_witness_rejects_wrong_init() {
+        let aig = shift();
+        let witness = AigWitness::parse("1\nb0\n01\n0\n.\n", &aig).unwrap();
+        assert!(!aig.check_witness(&witness));
+    }
+}
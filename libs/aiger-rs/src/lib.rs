@@ -1,7 +1,7 @@
 //! AIGER (And-Inverter graph) file format parser
 #![deny(missing_docs)]
 
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Read, Write};
 use std::str::FromStr;
 
 /// A literal value from an AIGER file, encoding both a variable index and "sign
@@ -45,6 +45,13 @@ pub struct Header {
     pub b: usize,
     /// The invariant constraints.
     pub c: usize,
+    /// The justice properties.
+    pub j: usize,
+    /// The fairness constraints.
+    pub f: usize,
+    /// True if the file is in the binary `aig` format, false if it is in the
+    /// ASCII `aag` format.
+    pub binary: bool,
 }
 
 impl FromStr for Header {
@@ -54,37 +61,44 @@ impl FromStr for Header {
     // convention the format itself uses, we'll use the M I L O A names.
     #[allow(clippy::many_single_char_names)]
     fn from_str(header_line: &str) -> Result<Self, Self::Err> {
-        let mut components = header_line.split(' ');
-        let magic = components.next().ok_or(AigerError::InvalidHeader)?;
-
-        const HEADER_MAGIC: &str = "aag";
-        if magic != HEADER_MAGIC {
-            return Err(AigerError::InvalidHeader);
-        }
+        let invalid = || AigerError::new(AigerErrorKind::InvalidHeader, None, header_line);
+        let mut components = header_line.split_whitespace();
+        let magic = components.next().ok_or_else(invalid)?;
+
+        const ASCII_HEADER_MAGIC: &str = "aag";
+        const BINARY_HEADER_MAGIC: &str = "aig";
+        let binary = match magic {
+            ASCII_HEADER_MAGIC => false,
+            BINARY_HEADER_MAGIC => true,
+            _ => return Err(invalid()),
+        };
 
-        let mut components =
-            components.map(|s| usize::from_str_radix(s, 10).map_err(|_| AigerError::InvalidHeader));
+        let mut components = components.map(|s| s.parse::<usize>().map_err(|_| invalid()));
 
         // The remaining components of the header are all integers
-        let mut get_component = || components.next().ok_or(AigerError::InvalidHeader)?;
+        let mut get_component = || components.next().ok_or_else(invalid)?;
         let m = get_component()?;
         let i = get_component()?;
         let l = get_component()?;
         let o = get_component()?;
         let a = get_component()?;
-        let b = match get_component() {
-            Ok(b) => b,
-            Err(_) => 0,
-        };
-        let c = match get_component() {
-            Ok(c) => c,
-            Err(_) => 0,
-        };
-
-        if components.next() != None {
+        // The B C J F components were added in AIGER 1.9 and may be missing,
+        // but if they are present they have to be valid
+        let mut get_optional_component =
+            || components.next().transpose().map(Option::unwrap_or_default);
+        let b = get_optional_component()?;
+        let c = get_optional_component()?;
+        let j = get_optional_component()?;
+        let f = get_optional_component()?;
+
+        if components.next().is_some() {
             // We have extra components after what should've been the last
             // component
-            Err(AigerError::InvalidHeader)
+            Err(invalid())
+        } else if binary && m != i + l + a {
+            // The variables of the binary format are implicit, so there can be
+            // no gaps or unused variables
+            Err(invalid())
         } else {
             Ok(Header {
                 m,
@@ -94,11 +108,33 @@ impl FromStr for Header {
                 a,
                 b,
                 c,
+                j,
+                f,
+                binary,
             })
         }
     }
 }
 
+impl std::fmt::Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let magic = if self.binary { "aig" } else { "aag" };
+        write!(
+            f,
+            "{} {} {} {} {} {}",
+            magic, self.m, self.i, self.l, self.o, self.a
+        )?;
+        // Trailing zero fields are optional and only written if a later field
+        // is nonzero.
+        let optional = [self.b, self.c, self.j, self.f];
+        let len = optional.iter().rposition(|n| *n > 0).map_or(0, |p| p + 1);
+        for n in &optional[..len] {
+            write!(f, " {}", n)?;
+        }
+        Ok(())
+    }
+}
+
 /// The type specifier for a symbol table entry.
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
 pub enum Symbol {
@@ -110,6 +146,12 @@ pub enum Symbol {
     Output,
     /// The symbol names a bad state.
     BadState,
+    /// The symbol names an invariant constraint.
+    Constraint,
+    /// The symbol names a justice property.
+    Justice,
+    /// The symbol names a fairness constraint.
+    Fairness,
 }
 
 /// A record from an AIGER file.
@@ -132,6 +174,11 @@ pub enum Aiger {
     BadState(Literal),
     /// A literal marked as a invariant constraint.
     Constraint(Literal),
+    /// A justice property, given by the set of literals which have to be true
+    /// infinitely often.
+    Justice(Vec<Literal>),
+    /// A literal marked as a fairness constraint.
+    Fairness(Literal),
     /// An AND gate.
     AndGate {
         /// The literal which receives the result of the AND operation.
@@ -174,130 +221,146 @@ pub enum Aiger {
 impl Aiger {
     /// Ensures the literals within the record are valid, returning the record
     /// if so or an error if one was detected.
-    fn validate(self, header_m: usize) -> Result<Aiger, AigerError> {
+    fn validate(self, header_m: usize) -> Result<Aiger, AigerErrorKind> {
         match self {
-            Aiger::Input(l) if l.is_inverted() => return Err(AigerError::InvalidInverted),
+            Aiger::Input(l) if l.is_inverted() => return Err(AigerErrorKind::InvalidInverted),
             Aiger::Latch { output, .. } if output.is_inverted() => {
-                return Err(AigerError::InvalidInverted)
+                return Err(AigerErrorKind::InvalidInverted)
             }
             Aiger::AndGate { output, .. } if output.is_inverted() => {
-                return Err(AigerError::InvalidInverted)
+                return Err(AigerErrorKind::InvalidInverted)
             }
             _ => {}
         }
 
-        let literals = match self {
-            Aiger::Input(l) => vec![l],
+        let literals = match &self {
+            Aiger::Input(l) => vec![*l],
             Aiger::Latch {
                 output,
                 input,
                 init,
             } => vec![
-                output,
-                input,
+                *output,
+                *input,
                 Literal(match init {
-                    Some(init) => init.into(),
+                    Some(init) => (*init).into(),
                     None => output.0,
                 }),
             ],
-            Aiger::Output(l) => vec![l],
-            Aiger::BadState(l) => vec![l],
-            Aiger::Constraint(l) => vec![l],
+            Aiger::Output(l) => vec![*l],
+            Aiger::BadState(l) => vec![*l],
+            Aiger::Constraint(l) => vec![*l],
+            Aiger::Justice(l) => l.clone(),
+            Aiger::Fairness(l) => vec![*l],
             Aiger::AndGate {
                 output,
                 inputs: [input0, input1],
-            } => vec![output, input0, input1],
+            } => vec![*output, *input0, *input1],
             Aiger::Symbol { .. } => return Ok(self),
         };
 
         for literal in literals {
             if literal.variable() > header_m {
-                return Err(AigerError::LiteralOutOfRange);
+                return Err(AigerErrorKind::LiteralOutOfRange);
             }
         }
 
         Ok(self)
     }
 
-    fn parse_input(literals: &[Literal]) -> Result<Aiger, AigerError> {
+    fn parse_input(literals: &[Literal]) -> Result<Aiger, AigerErrorKind> {
         match literals {
             [input] => Ok(Aiger::Input(*input)),
-            _ => Err(AigerError::InvalidLiteralCount),
+            _ => Err(AigerErrorKind::InvalidLiteralCount),
         }
     }
 
-    fn parse_latch(literals: &[Literal]) -> Result<Aiger, AigerError> {
+    fn parse_latch(literals: &[Literal]) -> Result<Aiger, AigerErrorKind> {
         match literals {
             [output, input] => Ok(Aiger::Latch {
                 output: *output,
                 input: *input,
                 init: Some(false),
             }),
-            [output, input, init] => Ok(Aiger::Latch {
-                output: *output,
-                input: *input,
-                init: if init.0 <= 1 {
+            [output, input, init] => {
+                let init = if init.0 <= 1 {
                     Some(init.0 != 0)
                 } else if init.0 == output.0 {
                     None
                 } else {
-                    panic!()
-                },
-            }),
-            _ => Err(AigerError::InvalidLiteralCount),
+                    return Err(AigerErrorKind::InvalidLatchInit);
+                };
+                Ok(Aiger::Latch {
+                    output: *output,
+                    input: *input,
+                    init,
+                })
+            }
+            _ => Err(AigerErrorKind::InvalidLiteralCount),
         }
     }
 
-    fn parse_output(literals: &[Literal]) -> Result<Aiger, AigerError> {
+    fn parse_output(literals: &[Literal]) -> Result<Aiger, AigerErrorKind> {
         match literals {
             [input] => Ok(Aiger::Output(*input)),
-            _ => Err(AigerError::InvalidLiteralCount),
+            _ => Err(AigerErrorKind::InvalidLiteralCount),
         }
     }
 
-    fn parse_badstate(literals: &[Literal]) -> Result<Aiger, AigerError> {
+    fn parse_badstate(literals: &[Literal]) -> Result<Aiger, AigerErrorKind> {
         match literals {
             [input] => Ok(Aiger::BadState(*input)),
-            _ => Err(AigerError::InvalidLiteralCount),
+            _ => Err(AigerErrorKind::InvalidLiteralCount),
         }
     }
 
-    fn parse_constraint(literals: &[Literal]) -> Result<Aiger, AigerError> {
+    fn parse_constraint(literals: &[Literal]) -> Result<Aiger, AigerErrorKind> {
         match literals {
             [input] => Ok(Aiger::Constraint(*input)),
-            _ => Err(AigerError::InvalidLiteralCount),
+            _ => Err(AigerErrorKind::InvalidLiteralCount),
+        }
+    }
+
+    fn parse_fairness(literals: &[Literal]) -> Result<Aiger, AigerErrorKind> {
+        match literals {
+            [input] => Ok(Aiger::Fairness(*input)),
+            _ => Err(AigerErrorKind::InvalidLiteralCount),
         }
     }
 
-    fn parse_and_gate(literals: &[Literal]) -> Result<Aiger, AigerError> {
+    fn parse_and_gate(literals: &[Literal]) -> Result<Aiger, AigerErrorKind> {
         match literals {
             [output, input1, input2] => Ok(Aiger::AndGate {
                 output: *output,
                 inputs: [*input1, *input2],
             }),
-            _ => Err(AigerError::InvalidLiteralCount),
+            _ => Err(AigerErrorKind::InvalidLiteralCount),
         }
     }
 
-    fn parse_symbol(line: &str) -> Result<Aiger, AigerError> {
-        let (type_spec, rest) = line.split_at(1);
-        let type_spec = match type_spec {
-            "i" => Symbol::Input,
-            "l" => Symbol::Latch,
-            "o" => Symbol::Output,
-            "b" => Symbol::BadState,
-            _ => return Err(AigerError::InvalidSymbol),
+    fn parse_symbol(line: &str) -> Result<Aiger, AigerErrorKind> {
+        let mut chars = line.chars();
+        let type_spec = match chars.next() {
+            Some('i') => Symbol::Input,
+            Some('l') => Symbol::Latch,
+            Some('o') => Symbol::Output,
+            Some('b') => Symbol::BadState,
+            Some('c') => Symbol::Constraint,
+            Some('j') => Symbol::Justice,
+            Some('f') => Symbol::Fairness,
+            _ => return Err(AigerErrorKind::InvalidSymbol),
         };
 
-        let space_position = rest.find(' ').ok_or(AigerError::InvalidSymbol)?;
-        let (position, rest) = rest.split_at(space_position);
-        let position =
-            usize::from_str_radix(position, 10).map_err(|_| AigerError::InvalidSymbol)?;
-
-        let (_, symbol) = rest.split_at(1);
+        let (position, symbol) = chars
+            .as_str()
+            .split_once(char::is_whitespace)
+            .ok_or(AigerErrorKind::InvalidSymbol)?;
+        let position = position
+            .parse::<usize>()
+            .map_err(|_| AigerErrorKind::InvalidSymbol)?;
 
         if symbol.is_empty() {
-            return Err(AigerError::InvalidSymbol);
+            return Err(AigerErrorKind::InvalidSymbol);
         }
 
         Ok(Aiger::Symbol {
@@ -308,9 +371,9 @@ impl Aiger {
     }
 }
 
-/// An error which occurs while parsing an AIGER file.
+/// The kind of an error which occurs while parsing an AIGER file.
 #[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
-pub enum AigerError {
+pub enum AigerErrorKind {
     /// No AIGER header could be found, or the header which was found could not
     /// be parsed.
     InvalidHeader,
@@ -327,13 +390,110 @@ pub enum AigerError {
     InvalidInverted,
     /// An invalid symbol table entry was encountered.
     InvalidSymbol,
+    /// An invalid delta was encountered in the AND gate section of a binary
+    /// file.
+    InvalidDelta,
+    /// A latch init value which is neither a constant nor the latch output was
+    /// encountered.
+    InvalidLatchInit,
+    /// A variable was defined more than once.
+    DuplicateDefinition,
+    /// A literal whose variable is never defined was used.
+    UndefinedLiteral,
+    /// The AND gates depend on each other cyclically.
+    CombinationalLoop,
     /// An IO error occurred while reading.
     IoError,
 }
 
-impl From<io::Error> for AigerError {
+impl From<io::Error> for AigerErrorKind {
     fn from(_error: io::Error) -> Self {
-        AigerError::IoError
+        AigerErrorKind::IoError
+    }
+}
+
+impl std::fmt::Display for AigerErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            AigerErrorKind::InvalidHeader => "invalid header",
+            AigerErrorKind::InvalidLiteral => "invalid literal",
+            AigerErrorKind::LiteralOutOfRange => "literal out of range",
+            AigerErrorKind::InvalidLiteralCount => "invalid literal count",
+            AigerErrorKind::InvalidInverted => "invalid inverted literal",
+            AigerErrorKind::InvalidSymbol => "invalid symbol",
+            AigerErrorKind::InvalidDelta => "invalid delta",
+            AigerErrorKind::InvalidLatchInit => "invalid latch init value",
+            AigerErrorKind::DuplicateDefinition => "duplicate definition",
+            AigerErrorKind::UndefinedLiteral => "undefined literal",
+            AigerErrorKind::CombinationalLoop => "combinational loop",
+            AigerErrorKind::IoError => "io error",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// An error which occurs while parsing an AIGER file, along with the position
+/// and the text at which it occurred.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct AigerError {
+    /// The kind of the error.
+    pub kind: AigerErrorKind,
+    /// The line number (starting from 1) at which the error occurred, or
+    /// `None` if the error can not be attributed to a line, e.g. in the binary
+    /// AND gate section.
+    pub line: Option<usize>,
+    /// The offending text.
+    pub text: String,
+}
+
+impl AigerError {
+    /// Creates a new error.
+    pub fn new(kind: AigerErrorKind, line: Option<usize>, text: impl Into<String>) -> Self {
+        Self {
+            kind,
+            line,
+            text: text.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for AigerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        write!(f, "{}", self.kind)?;
+        if !self.text.is_empty() {
+            write!(f, ": `{}`", self.text)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AigerError {}
+
+impl From<io::Error> for AigerError {
+    fn from(error: io::Error) -> Self {
+        AigerError::new(AigerErrorKind::IoError, None, error.to_string())
+    }
+}
+
+/// Reads a single line from the reader, stripping the trailing newline.
+/// Returns `None` once the end of the input has been reached.
+fn read_line<T: io::Read>(reader: &mut io::BufReader<T>) -> Option<io::Result<String>> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) => None,
+        Ok(_) => {
+            if line.ends_with('\n') {
+                line.pop();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+            }
+            Some(Ok(line))
+        }
+        Err(e) => Some(Err(e)),
     }
 }
 
@@ -342,7 +502,7 @@ impl From<io::Error> for AigerError {
 pub struct Reader<T: io::Read> {
     /// The AIGER header which was parsed during reader construction.
     header: Header,
-    lines: io::Lines<io::BufReader<T>>,
+    reader: io::BufReader<T>,
 }
 
 impl<T: io::Read> std::fmt::Debug for Reader<T> {
@@ -354,7 +514,8 @@ impl<T: io::Read> std::fmt::Debug for Reader<T> {
 }
 
 impl<T: io::Read> Reader<T> {
-    /// Creates a new AIGER reader which reads from the provided reader.
+    /// Creates a new AIGER reader which reads from the provided reader. Both
+    /// the ASCII (`aag`) and the binary (`aig`) formats are accepted.
     ///
     /// # Example
     /// ```
@@ -369,19 +530,24 @@ impl<T: io::Read> Reader<T> {
     /// }
     /// ```
     pub fn from_reader(reader: T) -> Result<Reader<T>, AigerError> {
-        let reader = io::BufReader::new(reader);
-        let mut lines = reader.lines();
+        let mut reader = io::BufReader::new(reader);
 
-        let header_line = lines.next().ok_or(AigerError::InvalidHeader)??;
-        let header = header_line.parse::<Header>()?;
+        let header_line = match read_line(&mut reader) {
+            Some(line) => line?,
+            None => return Err(AigerError::new(AigerErrorKind::InvalidHeader, Some(1), "")),
+        };
+        let header = header_line.parse::<Header>().map_err(|mut e| {
+            e.line = Some(1);
+            e
+        })?;
 
-        Ok(Reader { header, lines })
+        Ok(Reader { header, reader })
     }
 
     /// Returns an iterator over the records in the AIGER file, consuming the
     /// reader.
     pub fn records(self) -> RecordsIter<T> {
-        RecordsIter::new(self.lines, self.header)
+        RecordsIter::new(self.reader, self.header)
     }
 
     /// Returns the AIGER header.
@@ -394,8 +560,8 @@ impl<T: io::Read> Reader<T> {
 pub struct RecordsIter<T: io::Read> {
     /// The header of the AIGER file.
     header: Header,
-    /// An iterator over the lines of the AIGER file.
-    lines: io::Lines<io::BufReader<T>>,
+    /// The underlying reader of the AIGER file.
+    reader: io::BufReader<T>,
     /// Number of inputs which are yet to be parsed.
     remaining_inputs: usize,
     /// Number of outputs which are yet to be parsed.
@@ -408,14 +574,25 @@ pub struct RecordsIter<T: io::Read> {
     remaining_bad_states: usize,
     /// Number of constraints which are yet to be parsed.
     remaining_constraints: usize,
+    /// Number of justice properties which are yet to be parsed.
+    remaining_justice: usize,
+    /// Number of fairness constraints which are yet to be parsed.
+    remaining_fairness: usize,
+    /// The sizes of the justice properties, which are read along with the
+    /// first justice property.
+    justice_sizes: Vec<usize>,
     /// True if we have reached a comment in the file.
     comment_reached: bool,
+    /// Number of lines which have been read, including the header.
+    line: usize,
+    /// The last line which has been read.
+    text: String,
 }
 
 impl<T: io::Read> RecordsIter<T> {
-    fn new(lines: io::Lines<io::BufReader<T>>, header: Header) -> RecordsIter<T> {
+    fn new(reader: io::BufReader<T>, header: Header) -> RecordsIter<T> {
         RecordsIter {
-            lines,
+            reader,
             header,
             remaining_inputs: header.i,
             remaining_latches: header.l,
@@ -423,17 +600,52 @@ impl<T: io::Read> RecordsIter<T> {
             remaining_and_gates: header.a,
             remaining_bad_states: header.b,
             remaining_constraints: header.c,
+            remaining_justice: header.j,
+            remaining_fairness: header.f,
+            justice_sizes: Vec::new(),
             comment_reached: false,
+            line: 1,
+            text: String::new(),
         }
     }
 
-    fn read_record(&mut self, line: &str) -> Result<Aiger, AigerError> {
-        let get_literals = || -> Result<Vec<Literal>, AigerError> {
-            Ok(line
-                .split(' ')
-                .map(|s| usize::from_str_radix(s, 10).map(Literal))
+    /// Reads a single line, keeping track of the line number and the text for
+    /// error reporting.
+    fn read_line(&mut self) -> Option<io::Result<String>> {
+        let line = read_line(&mut self.reader);
+        if let Some(Ok(line)) = &line {
+            self.line += 1;
+            self.text.clone_from(line);
+        }
+        line
+    }
+
+    /// Creates an error positioned at the last line which has been read. This
+    /// can be used to report errors in records which are only detected by
+    /// the caller, such as a variable which is defined twice.
+    pub fn error(&self, kind: AigerErrorKind) -> AigerError {
+        AigerError::new(kind, Some(self.line), self.text.as_str())
+    }
+
+    /// Returns true if there are records left which are declared in the
+    /// header.
+    fn records_remaining(&self) -> bool {
+        self.remaining_inputs > 0
+            || self.remaining_latches > 0
+            || self.remaining_outputs > 0
+            || self.remaining_bad_states > 0
+            || self.remaining_constraints > 0
+            || self.remaining_justice > 0
+            || self.remaining_fairness > 0
+            || self.remaining_and_gates > 0
+    }
+
+    fn read_record(&mut self, line: &str) -> Result<Aiger, AigerErrorKind> {
+        let get_literals = || -> Result<Vec<Literal>, AigerErrorKind> {
+            line.split_whitespace()
+                .map(|s| s.parse::<usize>().map(Literal))
                 .collect::<Result<Vec<_>, _>>()
-                .map_err(|_| AigerError::InvalidLiteral)?)
+                .map_err(|_| AigerErrorKind::InvalidLiteral)
         };
 
         if self.remaining_inputs > 0 {
@@ -441,7 +653,14 @@ impl<T: io::Read> RecordsIter<T> {
             Aiger::parse_input(&get_literals()?)
         } else if self.remaining_latches > 0 {
             self.remaining_latches -= 1;
-            Aiger::parse_latch(&get_literals()?)
+            let mut literals = get_literals()?;
+            if self.header.binary {
+                // The current state literal of a latch is implicit in the
+                // binary format.
+                let variable = self.header.i + self.header.l - self.remaining_latches;
+                literals.insert(0, Literal::from_variable(variable, false));
+            }
+            Aiger::parse_latch(&literals)
         } else if self.remaining_outputs > 0 {
             self.remaining_outputs -= 1;
             Aiger::parse_output(&get_literals()?)
@@ -451,6 +670,9 @@ impl<T: io::Read> RecordsIter<T> {
         } else if self.remaining_constraints > 0 {
             self.remaining_constraints -= 1;
             Aiger::parse_constraint(&get_literals()?)
+        } else if self.remaining_fairness > 0 {
+            self.remaining_fairness -= 1;
+            Aiger::parse_fairness(&get_literals()?)
         } else if self.remaining_and_gates > 0 {
             self.remaining_and_gates -= 1;
             Aiger::parse_and_gate(&get_literals()?)
@@ -458,6 +680,112 @@ impl<T: io::Read> RecordsIter<T> {
             Aiger::parse_symbol(line)
         }
     }
+
+    /// Returns true if the next record is a justice property.
+    fn next_is_justice(&self) -> bool {
+        self.remaining_inputs == 0
+            && self.remaining_latches == 0
+            && self.remaining_outputs == 0
+            && self.remaining_bad_states == 0
+            && self.remaining_constraints == 0
+            && self.remaining_justice > 0
+    }
+
+    /// Reads a line containing a single number.
+    fn read_number(&mut self) -> Result<usize, AigerErrorKind> {
+        let line = self
+            .read_line()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))??;
+        line.trim()
+            .parse()
+            .map_err(|_| AigerErrorKind::InvalidLiteral)
+    }
+
+    /// Reads a justice property, which spans multiple lines. The sizes of all
+    /// justice properties precede their literals, so they are read along with
+    /// the first justice property.
+    fn read_justice(&mut self) -> Result<Aiger, AigerErrorKind> {
+        if self.justice_sizes.is_empty() {
+            for _ in 0..self.header.j {
+                let size = self.read_number()?;
+                self.justice_sizes.push(size);
+            }
+        }
+        let size = self.justice_sizes[self.header.j - self.remaining_justice];
+        self.remaining_justice -= 1;
+        let literals = (0..size)
+            .map(|_| self.read_number().map(Literal))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Aiger::Justice(literals))
+    }
+
+    /// Reads a single variable-length encoded delta of the binary AND gate
+    /// section.
+    fn read_delta(&mut self) -> Result<usize, AigerErrorKind> {
+        let mut delta = 0;
+        let mut shift = 0;
+        loop {
+            let mut byte = [0; 1];
+            self.reader.read_exact(&mut byte)?;
+            // Keep the line count of the records after the binary section
+            // right
+            if byte[0] == b'\n' {
+                self.line += 1;
+            }
+            if shift >= usize::BITS {
+                return Err(AigerErrorKind::InvalidDelta);
+            }
+            delta |= ((byte[0] & 0x7f) as usize) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(delta);
+            }
+            shift += 7;
+        }
+    }
+
+    /// Reads an AND gate of the binary format, whose output literal is
+    /// implicit and whose input literals are stored as deltas.
+    fn read_binary_and_gate(&mut self) -> Result<Aiger, AigerErrorKind> {
+        self.remaining_and_gates -= 1;
+        let variable = self.header.i + self.header.l + self.header.a - self.remaining_and_gates;
+        let output = Literal::from_variable(variable, false);
+        let delta0 = self.read_delta()?;
+        let delta1 = self.read_delta()?;
+        if delta0 == 0 || delta0 > output.0 || delta1 > output.0 - delta0 {
+            return Err(AigerErrorKind::InvalidDelta);
+        }
+        let input0 = Literal(output.0 - delta0);
+        let input1 = Literal(input0.0 - delta1);
+        Ok(Aiger::AndGate {
+            output,
+            inputs: [input0, input1],
+        })
+    }
+
+    /// Returns true if the next record of a binary file is not stored in the
+    /// file but implied by the header, or is part of the binary AND gate
+    /// section.
+    fn next_is_binary(&self) -> bool {
+        self.header.binary
+            && (self.remaining_inputs > 0
+                || (self.remaining_latches == 0
+                    && self.remaining_outputs == 0
+                    && self.remaining_bad_states == 0
+                    && self.remaining_constraints == 0
+                    && self.remaining_justice == 0
+                    && self.remaining_fairness == 0
+                    && self.remaining_and_gates > 0))
+    }
+
+    fn read_binary_record(&mut self) -> Result<Aiger, AigerErrorKind> {
+        if self.remaining_inputs > 0 {
+            self.remaining_inputs -= 1;
+            let variable = self.header.i - self.remaining_inputs;
+            Ok(Aiger::Input(Literal::from_variable(variable, false)))
+        } else {
+            self.read_binary_and_gate()
+        }
+    }
 }
 
 impl<T: io::Read> Iterator for RecordsIter<T> {
@@ -468,36 +796,325 @@ impl<T: io::Read> Iterator for RecordsIter<T> {
             return None;
         }
 
-        let line = match self.lines.next() {
-            Some(line) => line,
-            None => return None,
-        };
+        if self.next_is_binary() {
+            let record = if self.remaining_inputs > 0 {
+                format!("implicit input {}", self.header.i - self.remaining_inputs)
+            } else {
+                format!(
+                    "binary and gate {}",
+                    self.header.a - self.remaining_and_gates
+                )
+            };
+            return Some(
+                self.read_binary_record()
+                    .and_then(|record| record.validate(self.header.m))
+                    .map_err(|kind| AigerError::new(kind, None, record)),
+            );
+        }
+
+        if self.next_is_justice() {
+            return Some(
+                self.read_justice()
+                    .and_then(|record| record.validate(self.header.m))
+                    .map_err(|kind| self.error(kind)),
+            );
+        }
 
-        let line = match line {
-            Ok(line) => line,
-            Err(e) => return Some(Err(e.into())),
+        let line = match self.read_line() {
+            Some(Ok(line)) => line,
+            Some(Err(e)) => return Some(Err(self.error(e.into()))),
+            None if self.records_remaining() => {
+                return Some(Err(AigerError::new(
+                    AigerErrorKind::IoError,
+                    None,
+                    "unexpected end of file",
+                )))
+            }
+            None => return None,
         };
 
-        if let Some('c') = line.chars().next() {
+        // A `c` followed by a position is a constraint symbol rather than the
+        // start of the comment section.
+        let mut chars = line.chars();
+        if !self.records_remaining()
+            && chars.next() == Some('c')
+            && !chars.next().is_some_and(|c| c.is_ascii_digit())
+        {
             self.comment_reached = true;
             return None;
         }
 
         Some(
             self.read_record(&line)
-                .and_then(|record| record.validate(self.header.m)),
+                .and_then(|record| record.validate(self.header.m))
+                .map_err(|kind| self.error(kind)),
         )
     }
 }
 
+/// A wrapper around a type implementing `io::Write` which writes an AIGER
+/// header and AIGER records.
+///
+/// Records have to be written in the order in which they appear in an AIGER
+/// file: inputs, latches, outputs, bad states, constraints, justice
+/// properties, fairness constraints, AND gates and finally symbols. In the
+/// binary format the literals of inputs, latches and AND gates are implicit,
+/// so they have to follow the binary numbering convention, and AND gate
+/// inputs have to be smaller than the output.
+pub struct Writer<T: io::Write> {
+    /// The AIGER header which was written during writer construction.
+    header: Header,
+    writer: io::BufWriter<T>,
+    /// Number of inputs which are yet to be written.
+    remaining_inputs: usize,
+    /// Number of latches which are yet to be written.
+    remaining_latches: usize,
+    /// Number of outputs which are yet to be written.
+    remaining_outputs: usize,
+    /// Number of bad states which are yet to be written.
+    remaining_bad_states: usize,
+    /// Number of constraints which are yet to be written.
+    remaining_constraints: usize,
+    /// Number of justice properties which are yet to be written.
+    remaining_justice: usize,
+    /// Number of fairness constraints which are yet to be written.
+    remaining_fairness: usize,
+    /// Number of AND gates which are yet to be written.
+    remaining_and_gates: usize,
+    /// The justice properties written so far. They are buffered until the
+    /// last one is written, since the sizes of all justice properties precede
+    /// their literals.
+    justice: Vec<Vec<Literal>>,
+}
+
+impl<T: io::Write> std::fmt::Debug for Writer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Writer")
+            .field("header", &self.header)
+            .finish()
+    }
+}
+
+fn unexpected_record(record: &Aiger) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("unexpected aiger record {:?}", record),
+    )
+}
+
+impl<T: io::Write> Writer<T> {
+    /// Creates a new AIGER writer which writes the header to the provided
+    /// writer. The format is selected by `header.binary`.
+    ///
+    /// # Example
+    /// ```
+    /// use gipsyh_aiger::{Aiger, Header, Literal, Writer};
+    /// let header = Header { m: 3, i: 2, l: 0, o: 1, a: 1, b: 0, c: 0, j: 0, f: 0, binary: false };
+    /// let mut writer = Writer::from_writer(Vec::new(), header).unwrap();
+    /// writer.write_record(&Aiger::Input(Literal(2))).unwrap();
+    /// writer.write_record(&Aiger::Input(Literal(4))).unwrap();
+    /// writer.write_record(&Aiger::Output(Literal(6))).unwrap();
+    /// writer
+    ///     .write_record(&Aiger::AndGate {
+    ///         output: Literal(6),
+    ///         inputs: [Literal(2), Literal(4)],
+    ///     })
+    ///     .unwrap();
+    /// let written = writer.into_inner().unwrap();
+    /// assert_eq!(written, b"aag 3 2 0 1 1\n2\n4\n6\n6 2 4\n");
+    /// ```
+    pub fn from_writer(writer: T, header: Header) -> io::Result<Writer<T>> {
+        let mut writer = io::BufWriter::new(writer);
+        writeln!(writer, "{}", header)?;
+        Ok(Writer {
+            header,
+            writer,
+            remaining_inputs: header.i,
+            remaining_latches: header.l,
+            remaining_outputs: header.o,
+            remaining_bad_states: header.b,
+            remaining_constraints: header.c,
+            remaining_justice: header.j,
+            remaining_fairness: header.f,
+            remaining_and_gates: header.a,
+            justice: Vec::new(),
+        })
+    }
+
+    /// Returns the AIGER header.
+    pub fn header(&self) -> Header {
+        self.header
+    }
+
+    /// Returns the counters of the records which are yet to be written, in the
+    /// order in which the sections appear in the file.
+    fn remaining(&mut self) -> [&mut usize; 8] {
+        [
+            &mut self.remaining_inputs,
+            &mut self.remaining_latches,
+            &mut self.remaining_outputs,
+            &mut self.remaining_bad_states,
+            &mut self.remaining_constraints,
+            &mut self.remaining_justice,
+            &mut self.remaining_fairness,
+            &mut self.remaining_and_gates,
+        ]
+    }
+
+    /// Writes a single record.
+    pub fn write_record(&mut self, record: &Aiger) -> io::Result<()> {
+        let section = match record {
+            Aiger::Input(_) => 0,
+            Aiger::Latch { .. } => 1,
+            Aiger::Output(_) => 2,
+            Aiger::BadState(_) => 3,
+            Aiger::Constraint(_) => 4,
+            Aiger::Justice(_) => 5,
+            Aiger::Fairness(_) => 6,
+            Aiger::AndGate { .. } => 7,
+            Aiger::Symbol { .. } => 8,
+        };
+        let mut remaining = self.remaining();
+        if remaining[..section].iter().any(|r| **r > 0)
+            || remaining.get(section).is_some_and(|r| **r == 0)
+        {
+            return Err(unexpected_record(record));
+        }
+        if let Some(r) = remaining.get_mut(section) {
+            **r -= 1;
+        }
+        let binary = self.header.binary;
+        match record {
+            Aiger::Input(input) => {
+                if binary {
+                    let variable = self.header.i - self.remaining_inputs;
+                    if *input != Literal::from_variable(variable, false) {
+                        return Err(unexpected_record(record));
+                    }
+                    Ok(())
+                } else {
+                    writeln!(self.writer, "{}", input.0)
+                }
+            }
+            Aiger::Latch {
+                output,
+                input,
+                init,
+            } => {
+                if binary {
+                    let variable = self.header.i + self.header.l - self.remaining_latches;
+                    if *output != Literal::from_variable(variable, false) {
+                        return Err(unexpected_record(record));
+                    }
+                } else {
+                    write!(self.writer, "{} ", output.0)?;
+                }
+                match init {
+                    Some(false) => writeln!(self.writer, "{}", input.0),
+                    Some(true) => writeln!(self.writer, "{} 1", input.0),
+                    None => writeln!(self.writer, "{} {}", input.0, output.0),
+                }
+            }
+            Aiger::Output(l) | Aiger::BadState(l) | Aiger::Constraint(l) | Aiger::Fairness(l) => {
+                writeln!(self.writer, "{}", l.0)
+            }
+            Aiger::Justice(literals) => {
+                self.justice.push(literals.clone());
+                if self.remaining_justice > 0 {
+                    return Ok(());
+                }
+                for justice in self.justice.iter() {
+                    writeln!(self.writer, "{}", justice.len())?;
+                }
+                for literal in self.justice.iter().flatten() {
+                    writeln!(self.writer, "{}", literal.0)?;
+                }
+                Ok(())
+            }
+            Aiger::AndGate { output, inputs } => {
+                if binary {
+                    let variable =
+                        self.header.i + self.header.l + self.header.a - self.remaining_and_gates;
+                    let [input0, input1] = if inputs[0] >= inputs[1] {
+                        *inputs
+                    } else {
+                        [inputs[1], inputs[0]]
+                    };
+                    if *output != Literal::from_variable(variable, false) || input0 >= *output {
+                        return Err(unexpected_record(record));
+                    }
+                    self.write_delta(output.0 - input0.0)?;
+                    self.write_delta(input0.0 - input1.0)
+                } else {
+                    writeln!(self.writer, "{} {} {}", output.0, inputs[0].0, inputs[1].0)
+                }
+            }
+            Aiger::Symbol {
+                type_spec,
+                position,
+                symbol,
+            } => {
+                let type_spec = match type_spec {
+                    Symbol::Input => 'i',
+                    Symbol::Latch => 'l',
+                    Symbol::Output => 'o',
+                    Symbol::BadState => 'b',
+                    Symbol::Constraint => 'c',
+                    Symbol::Justice => 'j',
+                    Symbol::Fairness => 'f',
+                };
+                writeln!(self.writer, "{}{} {}", type_spec, position, symbol)
+            }
+        }
+    }
+
+    /// Writes a single variable-length encoded delta of the binary AND gate
+    /// section.
+    fn write_delta(&mut self, mut delta: usize) -> io::Result<()> {
+        while delta & !0x7f != 0 {
+            self.writer.write_all(&[(delta & 0x7f) as u8 | 0x80])?;
+            delta >>= 7;
+        }
+        self.writer.write_all(&[delta as u8])
+    }
+
+    /// Writes the comment section. This has to be the last call on the writer.
+    pub fn write_comment(&mut self, comment: &str) -> io::Result<()> {
+        writeln!(self.writer, "c")?;
+        writeln!(self.writer, "{}", comment)
+    }
+
+    /// Flushes the writer and returns the underlying writer. Fails if fewer
+    /// records were written than declared in the header.
+    pub fn into_inner(mut self) -> io::Result<T> {
+        if self.remaining().iter().any(|r| **r > 0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "missing aiger records",
+            ));
+        }
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn make_reader(s: &'static str) -> Result<Reader<&[u8]>, AigerError> {
+    fn make_reader(s: &'static str) -> Result<Reader<&'static [u8]>, AigerError> {
         Reader::from_reader(s.as_bytes())
     }
 
+    fn error_kind(
+        record: Option<Result<Aiger, AigerError>>,
+    ) -> Option<Result<Aiger, AigerErrorKind>> {
+        record.map(|record| record.map_err(|e| e.kind))
+    }
+
+    fn make_binary_reader(s: &[&[u8]]) -> Result<Reader<io::Cursor<Vec<u8>>>, AigerError> {
+        Reader::from_reader(io::Cursor::new(s.concat()))
+    }
+
     #[test]
     fn literal() {
         for (literal, variable, is_inverted) in &[
@@ -519,42 +1136,37 @@ mod tests {
 
     #[test]
     fn reader_no_header() {
-        #[rustfmt::skip]
-            let reader = make_reader(concat!(
-                "",
-            )).unwrap_err();
+        let reader = make_reader("").unwrap_err();
 
-        assert_eq!(reader, AigerError::InvalidHeader);
+        assert_eq!(reader.kind, AigerErrorKind::InvalidHeader);
     }
 
     #[test]
     fn reader_header_invalid_magic() {
-        #[rustfmt::skip]
-            let reader = make_reader(concat!(
-                "axg 0 0 0 0 0\n",
-            )).unwrap_err();
+        let reader = make_reader("axg 0 0 0 0 0\n").unwrap_err();
 
-        assert_eq!(reader, AigerError::InvalidHeader);
+        assert_eq!(reader.kind, AigerErrorKind::InvalidHeader);
     }
 
     #[test]
     fn reader_header_too_short() {
-        #[rustfmt::skip]
-            let reader = make_reader(concat!(
-                "aag 0 0 0 0\n",
-            )).unwrap_err();
+        let reader = make_reader("aag 0 0 0 0\n").unwrap_err();
 
-        assert_eq!(reader, AigerError::InvalidHeader);
+        assert_eq!(reader.kind, AigerErrorKind::InvalidHeader);
     }
 
     #[test]
     fn reader_header_invalid_value() {
-        #[rustfmt::skip]
-            let reader = make_reader(concat!(
-                "aag 0 q 0 0 0\n",
-            )).unwrap_err();
+        let reader = make_reader("aag 0 q 0 0 0\n").unwrap_err();
+
+        assert_eq!(reader.kind, AigerErrorKind::InvalidHeader);
+    }
+
+    #[test]
+    fn reader_header_invalid_justice_count() {
+        let reader = make_reader("aag 0 0 0 0 0 0 0 q 0\n").unwrap_err();
 
-        assert_eq!(reader, AigerError::InvalidHeader);
+        assert_eq!(reader.kind, AigerErrorKind::InvalidHeader);
     }
 
     #[test]
@@ -576,11 +1188,17 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidLiteral)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidLiteral))
+        );
     }
 
     #[test]
@@ -605,6 +1223,9 @@ mod tests {
                 a: 1,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
@@ -612,7 +1233,10 @@ mod tests {
         assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
         assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(4)))));
         assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(6)))));
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidLiteralCount)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidLiteralCount))
+        );
     }
 
     #[test]
@@ -635,12 +1259,18 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
         assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidLiteralCount)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidLiteralCount))
+        );
     }
     #[test]
     fn reader_invalid_inverted_input() {
@@ -661,11 +1291,17 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidInverted)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidInverted))
+        );
     }
 
     #[test]
@@ -687,11 +1323,17 @@ mod tests {
                 a: 1,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidInverted)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidInverted))
+        );
     }
 
     #[test]
@@ -713,11 +1355,17 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidInverted)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidInverted))
+        );
     }
 
     #[test]
@@ -740,12 +1388,18 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
         assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
-        assert_eq!(records.next(), Some(Err(AigerError::LiteralOutOfRange)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::LiteralOutOfRange))
+        );
     }
 
     #[test]
@@ -768,12 +1422,18 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
         assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
-        assert_eq!(records.next(), Some(Err(AigerError::LiteralOutOfRange)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::LiteralOutOfRange))
+        );
     }
 
     #[test]
@@ -796,12 +1456,18 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
         assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
-        assert_eq!(records.next(), Some(Err(AigerError::LiteralOutOfRange)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::LiteralOutOfRange))
+        );
     }
 
     #[test]
@@ -825,13 +1491,19 @@ mod tests {
                 a: 1,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
         assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
         assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(4)))));
-        assert_eq!(records.next(), Some(Err(AigerError::LiteralOutOfRange)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::LiteralOutOfRange))
+        );
     }
 
     #[test]
@@ -854,12 +1526,18 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
         assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(0)))));
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidSymbol)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidSymbol))
+        );
     }
 
     #[test]
@@ -882,12 +1560,18 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
         assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(0)))));
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidSymbol)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidSymbol))
+        );
     }
 
     #[test]
@@ -910,12 +1594,18 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
         assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(0)))));
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidSymbol)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidSymbol))
+        );
     }
 
     #[test]
@@ -938,20 +1628,23 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
         let mut records = reader.records();
         assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(0)))));
-        assert_eq!(records.next(), Some(Err(AigerError::InvalidSymbol)));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidSymbol))
+        );
     }
 
     #[test]
     fn reader_empty_file() {
-        #[rustfmt::skip]
-        let reader = make_reader(concat!(
-            "aag 0 0 0 0 0\n",
-        )).unwrap();
+        let reader = make_reader("aag 0 0 0 0 0\n").unwrap();
 
         let header = reader.header();
         assert_eq!(
@@ -964,6 +1657,9 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
@@ -990,6 +1686,9 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
@@ -1017,6 +1716,9 @@ mod tests {
                 a: 0,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
@@ -1047,6 +1749,9 @@ mod tests {
                 a: 1,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
@@ -1086,6 +1791,9 @@ mod tests {
                 a: 1,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
@@ -1134,6 +1842,9 @@ mod tests {
                 a: 3,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
@@ -1230,6 +1941,9 @@ mod tests {
                 a: 4,
                 b: 0,
                 c: 0,
+                j: 0,
+                f: 0,
+                binary: false,
             }
         );
 
@@ -1316,4 +2030,709 @@ mod tests {
         );
         assert_eq!(records.next(), None);
     }
+
+    #[test]
+    fn reader_binary_and_gate() {
+        #[rustfmt::skip]
+        let reader = make_binary_reader(&[
+            b"aig 3 2 0 1 1\n" as &[u8],
+            b"6\n",
+            &[0x02, 0x02],
+        ]).unwrap();
+
+        let header = reader.header();
+        assert_eq!(
+            header,
+            Header {
+                m: 3,
+                i: 2,
+                l: 0,
+                o: 1,
+                a: 1,
+                b: 0,
+                c: 0,
+                j: 0,
+                f: 0,
+                binary: true,
+            }
+        );
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(4)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(6)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                inputs: [Literal(4), Literal(2)],
+                output: Literal(6),
+            }))
+        );
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn reader_binary_toggle_ff_en_rst() {
+        #[rustfmt::skip]
+        let reader = make_binary_reader(&[
+            b"aig 7 2 1 2 4\n" as &[u8],
+            b"14\n",
+            b"6\n",
+            b"7\n",
+            &[0x02, 0x04],
+            &[0x03, 0x02],
+            &[0x08, 0x02],
+            &[0x08, 0x02],
+            b"i0 enable\n",
+            b"i1 reset\n",
+            b"l0 latch_Q\n",
+            b"c\n",
+            b"comment\n",
+        ]).unwrap();
+
+        let header = reader.header();
+        assert_eq!(
+            header,
+            Header {
+                m: 7,
+                i: 2,
+                l: 1,
+                o: 2,
+                a: 4,
+                b: 0,
+                c: 0,
+                j: 0,
+                f: 0,
+                binary: true,
+            }
+        );
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(4)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Latch {
+                output: Literal(6),
+                input: Literal(14),
+                init: Some(false)
+            }))
+        );
+        assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(6)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(7)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(8),
+                inputs: [Literal(6), Literal(2)],
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(10),
+                inputs: [Literal(7), Literal(5)],
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(12),
+                inputs: [Literal(4), Literal(2)],
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(14),
+                inputs: [Literal(6), Literal(4)],
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Symbol {
+                position: 0,
+                type_spec: Symbol::Input,
+                symbol: "enable".to_string(),
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Symbol {
+                position: 1,
+                type_spec: Symbol::Input,
+                symbol: "reset".to_string(),
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Symbol {
+                position: 0,
+                type_spec: Symbol::Latch,
+                symbol: "latch_Q".to_string(),
+            }))
+        );
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn reader_binary_invalid_delta() {
+        #[rustfmt::skip]
+        let reader = make_binary_reader(&[
+            b"aig 2 1 0 1 1\n" as &[u8],
+            b"4\n",
+            &[0x00, 0x00],
+        ]).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(4)))));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidDelta))
+        );
+    }
+
+    #[test]
+    fn reader_binary_truncated() {
+        #[rustfmt::skip]
+        let reader = make_binary_reader(&[
+            b"aig 2 1 0 1 1\n" as &[u8],
+            b"4\n",
+            &[0x02],
+        ]).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(4)))));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::IoError))
+        );
+    }
+
+    #[test]
+    fn reader_binary_header_mismatch() {
+        let err = make_binary_reader(&[b"aig 4 2 0 1 1\n"]).unwrap_err();
+
+        assert_eq!(err.kind, AigerErrorKind::InvalidHeader);
+    }
+
+    #[test]
+    fn reader_binary_error_position() {
+        // The first delta is a newline byte, so the symbol is on line 4
+        #[rustfmt::skip]
+        let reader = make_binary_reader(&[
+            b"aig 6 5 0 1 1\n" as &[u8],
+            b"12\n",
+            &[0x0a, 0x00],
+            b"x0 foo\n",
+        ]).unwrap();
+
+        let mut records = reader.records().skip(6);
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(12),
+                inputs: [Literal(2), Literal(2)],
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Err(AigerError::new(
+                AigerErrorKind::InvalidSymbol,
+                Some(4),
+                "x0 foo"
+            )))
+        );
+    }
+
+    #[test]
+    fn reader_error_position() {
+        #[rustfmt::skip]
+        let reader = make_reader(concat!(
+            "aag 3 2 0 1 1\n",
+            "2\n",
+            "4\n",
+            "6\n",
+            "6 2 x\n",
+        )).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(4)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(6)))));
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(
+            err,
+            AigerError::new(AigerErrorKind::InvalidLiteral, Some(5), "6 2 x")
+        );
+        assert_eq!(err.to_string(), "line 5: invalid literal: `6 2 x`");
+
+        let err = make_reader("aag 3 2 0 1\n").unwrap_err();
+        assert_eq!(
+            err,
+            AigerError::new(AigerErrorKind::InvalidHeader, Some(1), "aag 3 2 0 1")
+        );
+    }
+
+    #[test]
+    fn reader_invalid_latch_init() {
+        #[rustfmt::skip]
+        let reader = make_reader(concat!(
+            "aag 2 0 2 0 0\n",
+            "2 2 4\n",
+            "4 4 4\n",
+        )).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidLatchInit))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Latch {
+                output: Literal(4),
+                input: Literal(4),
+                init: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn reader_whitespace() {
+        #[rustfmt::skip]
+        let reader = make_reader(concat!(
+            "aag  3\t2 0 1 1\n",
+            "2\n",
+            " 4\n",
+            "6\t\n",
+            "6  2\t4\n",
+            "i0\tx\n",
+        )).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(4)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Output(Literal(6)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(6),
+                inputs: [Literal(2), Literal(4)],
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Symbol {
+                position: 0,
+                type_spec: Symbol::Input,
+                symbol: "x".to_string(),
+            }))
+        );
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn reader_truncated() {
+        #[rustfmt::skip]
+        let reader = make_reader(concat!(
+            "aag 3 2 0 1 1\n",
+            "2\n",
+            "4\n",
+            "c\n",
+        )).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(4)))));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidLiteral))
+        );
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::IoError))
+        );
+    }
+
+    #[test]
+    fn reader_empty_symbol_line() {
+        #[rustfmt::skip]
+        let reader = make_reader(concat!(
+            "aag 1 1 0 0 0\n",
+            "2\n",
+            "\n",
+        )).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::InvalidSymbol))
+        );
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn reader_justice_fairness() {
+        #[rustfmt::skip]
+        let reader = make_reader(concat!(
+            "aag 5 1 1 0 3 1 1 2 1\n",
+            "2\n",
+            "4 10\n",
+            "6\n",
+            "3\n",
+            "2\n",
+            "1\n",
+            "4\n",
+            "8\n",
+            "5\n",
+            "9\n",
+            "6 5 3\n",
+            "8 4 2\n",
+            "10 9 7\n",
+            "j0 always_eventually\n",
+            "f0 fair\n",
+        )).unwrap();
+
+        let header = reader.header();
+        assert_eq!(
+            header,
+            Header {
+                m: 5,
+                i: 1,
+                l: 1,
+                o: 0,
+                a: 3,
+                b: 1,
+                c: 1,
+                j: 2,
+                f: 1,
+                binary: false,
+            }
+        );
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Latch {
+                output: Literal(4),
+                input: Literal(10),
+                init: Some(false)
+            }))
+        );
+        assert_eq!(records.next(), Some(Ok(Aiger::BadState(Literal(6)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Constraint(Literal(3)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Justice(vec![Literal(4), Literal(8)])))
+        );
+        assert_eq!(records.next(), Some(Ok(Aiger::Justice(vec![Literal(5)]))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Fairness(Literal(9)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(6),
+                inputs: [Literal(5), Literal(3)],
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(8),
+                inputs: [Literal(4), Literal(2)],
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(10),
+                inputs: [Literal(9), Literal(7)],
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Symbol {
+                position: 0,
+                type_spec: Symbol::Justice,
+                symbol: "always_eventually".to_string(),
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Symbol {
+                position: 0,
+                type_spec: Symbol::Fairness,
+                symbol: "fair".to_string(),
+            }))
+        );
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn reader_constraint_symbol() {
+        #[rustfmt::skip]
+        let reader = make_reader(concat!(
+            "aag 1 1 0 0 0 0 1\n",
+            "2\n",
+            "3\n",
+            "i0 x\n",
+            "c0 not_x\n",
+            "c\n",
+            "c0 comment\n",
+        )).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Constraint(Literal(3)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Symbol {
+                position: 0,
+                type_spec: Symbol::Input,
+                symbol: "x".to_string(),
+            }))
+        );
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Symbol {
+                position: 0,
+                type_spec: Symbol::Constraint,
+                symbol: "not_x".to_string(),
+            }))
+        );
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn reader_binary_justice_fairness() {
+        #[rustfmt::skip]
+        let reader = make_binary_reader(&[
+            b"aig 3 1 1 0 1 0 0 1 1\n" as &[u8],
+            b"6\n",
+            b"1\n",
+            b"7\n",
+            b"4\n",
+            &[0x02, 0x02],
+        ]).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::Latch {
+                output: Literal(4),
+                input: Literal(6),
+                init: Some(false)
+            }))
+        );
+        assert_eq!(records.next(), Some(Ok(Aiger::Justice(vec![Literal(7)]))));
+        assert_eq!(records.next(), Some(Ok(Aiger::Fairness(Literal(4)))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Aiger::AndGate {
+                output: Literal(6),
+                inputs: [Literal(4), Literal(2)],
+            }))
+        );
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn reader_justice_out_of_range() {
+        #[rustfmt::skip]
+        let reader = make_reader(concat!(
+            "aag 1 1 0 0 0 0 0 1\n",
+            "2\n",
+            "2\n",
+            "2\n",
+            "4\n",
+        )).unwrap();
+
+        let mut records = reader.records();
+        assert_eq!(records.next(), Some(Ok(Aiger::Input(Literal(2)))));
+        assert_eq!(
+            error_kind(records.next()),
+            Some(Err(AigerErrorKind::LiteralOutOfRange))
+        );
+    }
+
+    fn write_records(header: Header, records: &[Aiger]) -> io::Result<Vec<u8>> {
+        let mut writer = Writer::from_writer(Vec::new(), header)?;
+        for record in records {
+            writer.write_record(record)?;
+        }
+        writer.into_inner()
+    }
+
+    fn read_records(s: &[u8]) -> (Header, Vec<Aiger>) {
+        let reader = Reader::from_reader(s).unwrap();
+        let header = reader.header();
+        let records = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+        (header, records)
+    }
+
+    #[test]
+    fn writer_toggle_ff_en_rst() {
+        let ascii = concat!(
+            "aag 7 2 1 2 4\n",
+            "2\n",
+            "4\n",
+            "6 14\n",
+            "6\n",
+            "7\n",
+            "8 6 2\n",
+            "10 7 5\n",
+            "12 4 2\n",
+            "14 6 4\n",
+            "i0 enable\n",
+            "i1 reset\n",
+            "l0 latch_Q\n",
+        );
+        let (header, records) = read_records(ascii.as_bytes());
+        let written = write_records(header, &records).unwrap();
+        assert_eq!(written, ascii.as_bytes());
+
+        let binary_header = Header {
+            binary: true,
+            ..header
+        };
+        let written = write_records(binary_header, &records).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(written, [
+            b"aig 7 2 1 2 4\n" as &[u8],
+            b"14\n",
+            b"6\n",
+            b"7\n",
+            &[0x02, 0x04],
+            &[0x03, 0x02],
+            &[0x08, 0x02],
+            &[0x08, 0x02],
+            b"i0 enable\n",
+            b"i1 reset\n",
+            b"l0 latch_Q\n",
+        ].concat());
+        assert_eq!(read_records(&written), (binary_header, records));
+    }
+
+    #[test]
+    fn writer_latch_init_and_large_delta() {
+        let header = Header {
+            m: 200,
+            i: 1,
+            l: 2,
+            o: 0,
+            a: 197,
+            b: 1,
+            c: 1,
+            j: 0,
+            f: 0,
+            binary: true,
+        };
+        let mut records = vec![
+            Aiger::Input(Literal(2)),
+            Aiger::Latch {
+                output: Literal(4),
+                input: Literal(400),
+                init: Some(true),
+            },
+            Aiger::Latch {
+                output: Literal(6),
+                input: Literal(5),
+                init: None,
+            },
+            Aiger::BadState(Literal(401)),
+            Aiger::Constraint(Literal(3)),
+        ];
+        for variable in 4..=200 {
+            records.push(Aiger::AndGate {
+                output: Literal::from_variable(variable, false),
+                inputs: [Literal::from_variable(variable - 1, true), Literal(2)],
+            });
+        }
+        let written = write_records(header, &records).unwrap();
+        assert_eq!(read_records(&written), (header, records.clone()));
+
+        let header = Header {
+            binary: false,
+            ..header
+        };
+        let written = write_records(header, &records).unwrap();
+        assert_eq!(read_records(&written), (header, records));
+    }
+
+    #[test]
+    fn writer_justice_fairness() {
+        let ascii = concat!(
+            "aag 5 1 1 0 3 1 1 2 1\n",
+            "2\n",
+            "4 10\n",
+            "6\n",
+            "3\n",
+            "2\n",
+            "1\n",
+            "4\n",
+            "8\n",
+            "5\n",
+            "9\n",
+            "6 5 3\n",
+            "8 4 2\n",
+            "10 9 7\n",
+            "j0 always_eventually\n",
+            "f0 fair\n",
+        );
+        let (header, records) = read_records(ascii.as_bytes());
+        let written = write_records(header, &records).unwrap();
+        assert_eq!(written, ascii.as_bytes());
+
+        let binary_header = Header {
+            binary: true,
+            ..header
+        };
+        let written = write_records(binary_header, &records).unwrap();
+        assert_eq!(read_records(&written), (binary_header, records));
+    }
+
+    #[test]
+    fn writer_unexpected_record() {
+        let header = Header {
+            m: 3,
+            i: 2,
+            l: 0,
+            o: 1,
+            a: 1,
+            b: 0,
+            c: 0,
+            j: 0,
+            f: 0,
+            binary: true,
+        };
+        let err = write_records(
+            header,
+            &[Aiger::Input(Literal(2)), Aiger::Output(Literal(6))],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let err = write_records(
+            header,
+            &[Aiger::Input(Literal(4)), Aiger::Input(Literal(2))],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let err = write_records(
+            header,
+            &[
+                Aiger::Input(Literal(2)),
+                Aiger::Input(Literal(4)),
+                Aiger::Output(Literal(6)),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
 }
@@ -1,6 +1,7 @@
 use crate::frames::Frames;
 use crate::Ic3;
 use logic_form::{Cube, Var};
+use std::collections::HashMap;
 use std::{collections::HashSet, io::Read};
 use std::{fs::File, io::Write};
 
@@ -9,6 +10,16 @@ impl Ic3 {
         let json = serde_json::to_string(&self.frames).unwrap();
         let mut file = File::create("frames.json").unwrap();
         file.write_all(json.as_bytes()).unwrap();
+        let symbols: HashMap<u32, &String> = self
+            .share
+            .model
+            .symbols
+            .iter()
+            .map(|(v, name)| (**v, name))
+            .collect();
+        let json = serde_json::to_string(&symbols).unwrap();
+        let mut file = File::create("symbols.json").unwrap();
+        file.write_all(json.as_bytes()).unwrap();
     }
 }
 
@@ -19,6 +30,19 @@ fn read_frames() -> Frames {
     serde_json::from_str(&json).unwrap()
 }
 
+fn read_symbols() -> HashMap<Var, String> {
+    let Ok(mut file) = File::open("symbols.json") else {
+        return HashMap::new();
+    };
+    let mut json = String::new();
+    file.read_to_string(&mut json).unwrap();
+    let symbols: HashMap<u32, String> = serde_json::from_str(&json).unwrap();
+    symbols
+        .into_iter()
+        .map(|(v, name)| (Var::from(v), name))
+        .collect()
+}
+
 fn affinity(x: &Cube, y: &Cube) -> (f64, Cube) {
     let xs: HashSet<Var> = x.iter().map(|l| l.var()).collect();
     let ys: HashSet<Var> = y.iter().map(|l| l.var()).collect();
@@ -117,11 +141,13 @@ pub fn filter(inv: Vec<Cube>, f: &[Var]) -> Vec<Cube> {
 #[test]
 pub fn analysis() {
     let mut frames = read_frames();
+    let symbols = read_symbols();
     for i in 1..frames.len() {
         println!("frame {}", i);
         frames[i].sort();
         for c in frames[i].iter() {
-            println!("{:?}", c);
+            let name = |var| symbols.get(&var).map(|s: &String| s.as_str());
+            println!("{}", crate::model::lits_display(c, name));
         }
     }
 
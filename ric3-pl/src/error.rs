@@ -0,0 +1,28 @@
+use aig::{AigerError, MiterError, WitnessError};
+use std::fmt::{self, Display};
+
+/// An error which stops a command before it gives a result.
+#[derive(Debug)]
+pub enum Error {
+    /// An aiger file, given by its name, cannot be read.
+    Aiger(String, AigerError),
+    /// The models of `--cec` do not fit together.
+    Miter(MiterError),
+    /// A witness file, given by its name, cannot be read.
+    Witness(String, WitnessError),
+    /// The model file, given by its name, has no such property.
+    Property(String, usize),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Aiger(file, e) => write!(f, "{file}: {e}"),
+            Error::Miter(e) => write!(f, "cec: {e}"),
+            Error::Witness(file, e) => write!(f, "{file}: {e}"),
+            Error::Property(file, p) => write!(f, "{file}: property {p} does not exist"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -1,4 +1,4 @@
-use crate::Ic3;
+use crate::{model::Model, Ic3};
 use logic_form::Cube;
 use minisat::SatResult;
 use serde::{Deserialize, Serialize};
@@ -107,14 +107,28 @@ impl Ic3 {
     }
 }
 
-impl Display for Frames {
+pub struct FramesDisplay<'a> {
+    frames: &'a Frames,
+    model: &'a Model,
+}
+
+impl Frames {
+    pub fn display<'a>(&'a self, model: &'a Model) -> FramesDisplay<'a> {
+        FramesDisplay {
+            frames: self,
+            model,
+        }
+    }
+}
+
+impl Display for FramesDisplay<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for i in 1..self.frames.len() {
             f.write_fmt(format_args_nl!("frame {}", i))?;
             let mut frame = self.frames[i].clone();
             frame.sort();
             for c in frame.iter() {
-                f.write_fmt(format_args_nl!("{:?}", c))?;
+                f.write_fmt(format_args_nl!("{}", self.model.lits_display(c)))?;
             }
         }
         Ok(())
@@ -4,29 +4,43 @@ mod activity;
 #[allow(dead_code)]
 mod analysis;
 mod basic;
+mod cec;
 mod command;
+mod error;
 mod frames;
 mod mic;
 mod model;
+mod properties;
+mod regcorr;
+mod result;
 mod simulate;
 mod solver;
 mod statistic;
+mod trace;
 mod verify;
+mod witness;
 
 use crate::basic::ProofObligation;
 use crate::{basic::BasicShare, statistic::Statistic};
 use crate::{basic::ProofObligationQueue, solver::Lift};
 use activity::Activity;
-use aig::Aig;
+use aig::{Aig, AigEdge, AigTrace, AigWitness};
+pub use cec::cec;
 pub use command::Args;
+pub use error::Error;
 use frames::Frames;
 use logic_form::{Cube, Lit};
 use model::Model;
+pub use properties::check_all;
+pub use result::{CheckResult, UnknownReason};
 use solver::{BlockResult, Ic3Solver};
 use std::collections::HashMap;
+use std::mem::take;
 use std::panic::{self, AssertUnwindSafe};
-use std::process::exit;
-use std::{sync::Arc, time::Instant};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use std::{fs, rc::Rc, sync::Arc, time::Instant};
+pub use witness::check_witness;
 
 pub struct Ic3 {
     pub solvers: Vec<Ic3Solver>,
@@ -38,6 +52,30 @@ pub struct Ic3 {
     pub lift: Lift,
     pub statistic: Statistic,
     pub push_fail: HashMap<(Cube, usize), Cube>,
+    /// Concrete counterexample of the model file, if one has been found.
+    pub trace: Option<AigTrace>,
+    /// Why the check stops early, if it does.
+    pub stop: Option<UnknownReason>,
+    start: Instant,
+    num_polls: usize,
+    /// Lemmas of other checks, added to frame 1 once it exists.
+    reused_lemmas: Vec<Cube>,
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Stops the running and later checks with an unknown result. Returns true
+/// if they have already been stopped, e.g. to exit on a second ctrl-c.
+pub fn interrupt() -> bool {
+    INTERRUPTED.swap(true, Ordering::Relaxed)
+}
+
+/// Resident memory of the process in bytes, if it is known.
+fn resident_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb << 10)
 }
 
 impl Ic3 {
@@ -49,6 +87,11 @@ impl Ic3 {
         self.frames.new_frame();
         self.solvers
             .push(Ic3Solver::new(self.share.clone(), self.solvers.len()));
+        if self.depth() == 1 {
+            for lemma in take(&mut self.reused_lemmas) {
+                self.add_cube(1, lemma);
+            }
+        }
     }
 
     fn generalize(&mut self, frame: usize, cube: Cube) -> (usize, Cube) {
@@ -66,7 +109,8 @@ impl Ic3 {
                     }
                     let mut tmp = cube.clone();
                     tmp.sort();
-                    let cex = self.share.model.cube_previous(&cex);
+                    let mut cex = self.share.model.cube_previous(&cex);
+                    cex.sort();
                     debug_assert!(tmp.ordered_subsume(&cex));
                     self.push_fail.insert((tmp, i - 1), cex);
                     return (i, cube);
@@ -79,18 +123,31 @@ impl Ic3 {
     pub fn handle_blocked(&mut self, po: ProofObligation, conflict: Cube) {
         let (frame, core) = self.generalize(po.frame, conflict);
         if frame <= self.depth() {
-            self.obligations
-                .add(ProofObligation::new(frame, po.cube, po.depth, po.successor));
+            self.obligations.add(ProofObligation::new(
+                frame,
+                po.cube,
+                po.depth,
+                po.inputs,
+                po.successor,
+            ));
         }
         self.add_cube(frame - 1, core);
     }
 
-    pub fn block(&mut self, frame: usize, cube: Cube) -> bool {
+    /// Blocks the cube in the frame. Returns false if it reaches the initial
+    /// states, and true if it is blocked or the check stops.
+    pub fn block(&mut self, frame: usize, cube: Cube, inputs: Cube) -> bool {
         assert!(self.obligations.is_empty());
         self.obligations
-            .add(ProofObligation::new(frame, cube, 0, None));
+            .add(ProofObligation::new(frame, cube, 0, inputs, None));
         while let Some(po) = self.obligations.pop() {
+            if self.should_stop() {
+                self.obligations.clear();
+                return true;
+            }
             if po.frame == 0 {
+                let trace = self.po_trace(&po);
+                self.trace = self.original_trace(&trace);
                 return false;
             }
             assert!(!self.share.model.cube_subsume_init(&po.cube));
@@ -110,11 +167,13 @@ impl Ic3 {
                 }
                 BlockResult::No(unblocked) => {
                     let model = self.unblocked_model(&unblocked);
+                    let inputs = self.unblocked_model_inputs(&unblocked);
                     self.obligations.add(ProofObligation::new(
                         po.frame - 1,
                         model,
                         po.depth + 1,
-                        Some(po.cube.clone()),
+                        inputs,
+                        Some(Rc::new(po.clone())),
                     ));
                     self.obligations.add(po);
                 }
@@ -134,6 +193,9 @@ impl Ic3 {
             let mut frame = self.frames[frame_idx].clone();
             frame.sort_by_key(|x| x.len());
             for cube in frame {
+                if self.should_stop() {
+                    return false;
+                }
                 if !self.frames[frame_idx].contains(&cube) {
                     continue;
                 }
@@ -153,7 +215,8 @@ impl Ic3 {
                                 self.unblocked_model_lit_value(&unblock, p.lit()),
                             ));
                         }
-                        let cex = self.share.model.cube_previous(&cex);
+                        let mut cex = self.share.model.cube_previous(&cex);
+                        cex.sort();
                         debug_assert!(cube.ordered_subsume(&cex));
                         self.push_fail.insert((cube.clone(), frame_idx), cex);
                     }
@@ -169,16 +232,73 @@ impl Ic3 {
 }
 
 impl Ic3 {
-    pub fn new(args: Args) -> Self {
-        let aig = Aig::from_file(args.model.as_ref().unwrap()).unwrap();
-        let model = Model::from_aig(&aig);
-        let bad = Cube::from([if aig.bads.is_empty() {
-            aig.outputs[0]
-        } else {
-            aig.bads[0]
+    pub fn new(args: Args) -> Result<Self, Error> {
+        let start = Instant::now();
+        let model = args.model.as_ref().unwrap();
+        let mut aig = Aig::from_file(model).map_err(|e| Error::Aiger(model.clone(), e))?;
+        if args.property >= aig.properties().len() {
+            return Err(Error::Property(model.clone(), args.property));
         }
-        .to_lit()]);
+        let origin = aig.clone();
+        let statistic = Statistic::new(model, args.property, aig.property_name(args.property));
+        let mut merged = 0;
+        if args.fraig {
+            merged = aig.fraig();
+            if args.verbose {
+                println!("fraig: {merged} nodes merged");
+            }
+        }
+        let mut constant_latchs = 0;
+        if args.latch_reduce {
+            constant_latchs = aig.remove_constant_latchs();
+            if args.verbose {
+                println!("latch reduce: {constant_latchs} constant latchs");
+            }
+        }
+        let mut regcorr_removed = 0;
+        if args.regcorr {
+            regcorr_removed = regcorr::register_correspondence(&mut aig);
+            if args.verbose {
+                println!("register correspondence: {regcorr_removed} latchs merged");
+            }
+        }
+        if merged + constant_latchs + regcorr_removed > 0 {
+            aig.cleanup();
+        }
+        if args.rewrite {
+            let num_ands = aig.ands_iter().count();
+            aig = aig.rewrite().balance();
+            if args.verbose {
+                println!(
+                    "rewrite: {num_ands} -> {} and nodes",
+                    aig.ands_iter().count()
+                );
+            }
+        }
+        let mut bad = aig.properties()[args.property];
+        let mut coi_dropped = (0, 0);
+        if args.coi {
+            let (coi, _) = aig.coi_refine(bad);
+            bad = coi.bads[0];
+            coi_dropped = (
+                aig.inputs.len().saturating_sub(coi.inputs.len()),
+                aig.latchs.len() - coi.latchs.len(),
+            );
+            if args.verbose {
+                println!(
+                    "coi: dropped {} of {} inputs, {} of {} latchs",
+                    coi_dropped.0,
+                    aig.inputs.len(),
+                    coi_dropped.1,
+                    aig.latchs.len()
+                );
+            }
+            aig = coi;
+        }
+        let model = Model::from_aig(&aig, bad, args.cnf);
+        let bad = Cube::from([bad.to_lit()]);
         let share = Arc::new(BasicShare {
+            origin,
             aig,
             args,
             model,
@@ -190,11 +310,20 @@ impl Ic3 {
             activity: Activity::new(),
             cav23_activity: Activity::new(),
             lift: Lift::new(share.clone()),
-            statistic: Statistic::new(share.args.model.as_ref().unwrap()),
+            statistic,
             share,
             obligations: ProofObligationQueue::new(),
             push_fail: HashMap::new(),
+            trace: None,
+            stop: None,
+            start,
+            num_polls: 0,
+            reused_lemmas: Vec::new(),
         };
+        res.statistic.num_constant_latchs = constant_latchs;
+        res.statistic.num_regcorr_latchs = regcorr_removed;
+        res.statistic.num_coi_dropped_inputs = coi_dropped.0;
+        res.statistic.num_coi_dropped_latchs = coi_dropped.1;
         res.new_frame();
         for i in 0..res.share.aig.latchs.len() {
             let l = &res.share.aig.latchs[i];
@@ -203,18 +332,96 @@ impl Ic3 {
                 res.add_cube(0, cube)
             }
         }
-        res
+        Ok(res)
+    }
+
+    /// Random simulation for a bounded number of cycles, which records the
+    /// trace if it reaches the bad state.
+    fn random_simulate(&mut self) -> bool {
+        let aig = &self.share.aig;
+        let bad = AigEdge::from_lit(self.share.bad[0]);
+        let seed = self.share.args.random.unwrap_or(0) as u64;
+        let Some(trace) = aig.random_simulate_bad(bad, self.share.args.sim, seed) else {
+            return false;
+        };
+        if self.share.args.verbose {
+            println!(
+                "random simulation: bad reached in cycle {}",
+                trace.inputs.len() - 1
+            );
+        }
+        self.trace = self.original_trace(&trace);
+        self.trace.is_some()
+    }
+
+    fn limit_reached(&mut self) -> Option<UnknownReason> {
+        if INTERRUPTED.load(Ordering::Relaxed) {
+            return Some(UnknownReason::Interrupted);
+        }
+        let args = &self.share.args;
+        if let Some(limit) = args.time_limit {
+            if self.start.elapsed() >= Duration::from_secs(limit) {
+                return Some(UnknownReason::TimeLimit);
+            }
+        }
+        // reading the memory usage is costly compared to a poll
+        self.num_polls += 1;
+        if let Some(limit) = args.memory_limit {
+            if self.num_polls.is_multiple_of(64)
+                && resident_memory().is_some_and(|m| m >= limit << 20)
+            {
+                return Some(UnknownReason::MemoryLimit);
+            }
+        }
+        None
+    }
+
+    /// Returns true if the check should stop, recording the reason. Polled
+    /// between sat calls.
+    pub fn should_stop(&mut self) -> bool {
+        if self.stop.is_none() {
+            self.stop = self.limit_reached();
+        }
+        self.stop.is_some()
+    }
+
+    fn unknown(&mut self) -> CheckResult {
+        self.statistic();
+        CheckResult::Unknown {
+            reason: self.stop.unwrap(),
+            proven_bound: self.depth().checked_sub(1),
+        }
     }
 
-    fn check_inner(&mut self) -> bool {
+    /// The result once the initial states are reached, unknown if the
+    /// counterexample does not replay on the model file.
+    fn unsafe_result(&mut self) -> CheckResult {
+        match self.trace.clone() {
+            Some(trace) => {
+                self.statistic();
+                CheckResult::Unsafe(trace)
+            }
+            None => {
+                self.stop = Some(UnknownReason::InvalidCounterexample);
+                self.unknown()
+            }
+        }
+    }
+
+    fn check_inner(&mut self) -> CheckResult {
+        if self.share.args.sim > 0 && self.random_simulate() {
+            return self.unsafe_result();
+        }
         loop {
             let start = Instant::now();
             let mut trivial = true;
-            while let Some(cex) = self.get_bad() {
+            while let Some((cex, inputs)) = self.get_bad() {
                 trivial = false;
-                if !self.block(self.depth(), cex) {
-                    self.statistic();
-                    return false;
+                if !self.block(self.depth(), cex, inputs) {
+                    return self.unsafe_result();
+                }
+                if self.should_stop() {
+                    return self.unknown();
                 }
             }
             let blocked_time = start.elapsed();
@@ -232,27 +439,38 @@ impl Ic3 {
             let start = Instant::now();
             let propagate = self.propagate(trivial);
             self.statistic.overall_propagate_time += start.elapsed();
+            if self.should_stop() {
+                return self.unknown();
+            }
             if propagate {
                 self.statistic();
+                if self.share.args.verbose_all {
+                    print!("{}", self.frames.display(&self.share.model));
+                }
                 if self.share.args.save_frames {
                     self.save_frames();
                 }
                 if self.share.args.verify {
                     assert!(self.verify());
                 }
-                return true;
+                let invariant = self.frames.iter().position(|f| f.is_empty()).unwrap();
+                return CheckResult::Safe(self.frames[invariant..].concat());
             }
         }
     }
 
-    pub fn check(&mut self) -> bool {
-        let ic3 = self as *mut Ic3 as usize;
-        ctrlc::set_handler(move || {
-            let ic3 = unsafe { &mut *(ic3 as *mut Ic3) };
-            ic3.statistic();
-            exit(130);
+    /// Returns the witness of the counterexample of the model file, if one
+    /// has been found.
+    pub fn witness(&self) -> Option<AigWitness> {
+        self.trace.as_ref().map(|trace| AigWitness {
+            bads: vec![self.share.args.property],
+            trace: trace.clone(),
         })
-        .unwrap();
+    }
+
+    /// Checks the property. The check stops with an unknown result once
+    /// [`interrupt`] is called.
+    pub fn check(&mut self) -> CheckResult {
         panic::catch_unwind(AssertUnwindSafe(|| self.check_inner())).unwrap_or_else(|_| {
             self.statistic();
             panic!();
@@ -1,7 +1,42 @@
+use aig::AigWitness;
 use clap::Parser;
-use ic3::{Args, Ic3};
+use ic3::{Args, CheckResult, Error, Ic3};
+use std::{fs, process::exit};
+
+fn write_witness(file: &str, witness: &str) {
+    if let Err(e) = fs::write(file, witness) {
+        eprintln!("{file}: {e}");
+        exit(1);
+    }
+}
+
+fn unwrap_or_exit<T>(res: Result<T, Error>) -> T {
+    res.unwrap_or_else(|e| {
+        eprintln!("{e}");
+        exit(1);
+    })
+}
+
+/// The exit code of a true or false result, as for the checks.
+fn result_code(res: bool) -> i32 {
+    if res {
+        20
+    } else {
+        10
+    }
+}
 
 fn main() {
+    // the first ctrl-c stops the check with an unknown result, the second one
+    // exits
+    if let Err(e) = ctrlc::set_handler(|| {
+        if ic3::interrupt() {
+            exit(130);
+        }
+    }) {
+        eprintln!("{e}");
+        exit(1);
+    }
     let mut args = Args::parse();
     let aig = // Safe
     // "../MC-Benchmark/hwmcc20/aig/2019/beem/pgm_protocol.7.prop1-back-serstep.aag";
@@ -13,7 +48,48 @@ fn main() {
     if args.model.is_none() {
         args.model = Some(aig.to_string());
     }
+    if args.cec.is_some() {
+        exit(result_code(unwrap_or_exit(ic3::cec(&args))));
+    }
+    if args.check_witness.is_some() {
+        exit(result_code(unwrap_or_exit(ic3::check_witness(&args))));
+    }
+    if args.all_properties {
+        let witness = args.witness.clone();
+        let results = unwrap_or_exit(ic3::check_all(args));
+        if let Some(file) = witness {
+            // one entry per property: 0 if safe, 2 if unknown, and the
+            // counterexample if unsafe
+            let witnesses: String = results
+                .iter()
+                .enumerate()
+                .map(|(property, res)| match res {
+                    CheckResult::Safe(_) => "0\n".to_string(),
+                    CheckResult::Unsafe(trace) => AigWitness {
+                        bads: vec![property],
+                        trace: trace.clone(),
+                    }
+                    .to_string(),
+                    CheckResult::Unknown { .. } => "2\n".to_string(),
+                })
+                .collect();
+            write_witness(&file, &witnesses);
+        }
+        let code = if results.iter().any(|r| matches!(r, CheckResult::Unsafe(_))) {
+            10
+        } else if results.iter().all(|r| matches!(r, CheckResult::Safe(_))) {
+            20
+        } else {
+            0
+        };
+        exit(code);
+    }
 
-    let mut ic3 = Ic3::new(args);
-    println!("result: {}", ic3.check());
+    let mut ic3 = unwrap_or_exit(Ic3::new(args));
+    let res = ic3.check();
+    println!("result: {res}");
+    if let (Some(file), Some(witness)) = (&ic3.share.args.witness, ic3.witness()) {
+        write_witness(file, &witness.to_string());
+    }
+    exit(res.exit_code());
 }
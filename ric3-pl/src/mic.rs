@@ -288,7 +288,8 @@ impl Ic3 {
                                     self.unblocked_model_lit_value(&unblock, p.lit()),
                                 ));
                             }
-                            let cex = self.share.model.cube_previous(&cex);
+                            let mut cex = self.share.model.cube_previous(&cex);
+                            cex.sort();
                             self.push_fail.insert((p.clone(), frame - 1), cex);
                         }
                         DownResult::IncludeInit => (),
@@ -302,6 +303,9 @@ impl Ic3 {
         self.statistic.sr_adv.fail();
         let mut i = 0;
         while i < cube.len() {
+            if self.should_stop() {
+                break;
+            }
             let mut removed_cube = cube.clone();
             removed_cube.remove(i);
             let res = if simple {
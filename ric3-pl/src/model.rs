@@ -1,4 +1,4 @@
-use aig::Aig;
+use aig::{Aig, AigEdge, CnfEncoding};
 use logic_form::{Clause, Cnf, Cube, Lit, Var};
 use minisat::{SimpSolver, Solver};
 use std::collections::HashMap;
@@ -11,13 +11,14 @@ pub struct Model {
     pub constraints: Vec<Lit>,
     pub bad: Lit,
     pub trans: Cnf,
+    pub symbols: HashMap<Var, String>,
     num_var: usize,
     next_map: HashMap<Var, Var>,
     previous_map: HashMap<Var, Var>,
 }
 
 impl Model {
-    pub fn from_aig(aig: &Aig) -> Self {
+    pub fn from_aig(aig: &Aig, aig_bad: AigEdge, encoding: CnfEncoding) -> Self {
         let mut simp_solver = SimpSolver::new();
         let false_lit: Lit = simp_solver.new_var().into();
         simp_solver.add_clause(&[!false_lit]);
@@ -32,11 +33,6 @@ impl Model {
             init.insert(l.var(), l.polarity());
         }
         let constraints: Vec<Lit> = aig.constraints.iter().map(|c| c.to_lit()).collect();
-        let aig_bad = if aig.bads.is_empty() {
-            aig.outputs[0]
-        } else {
-            aig.bads[0]
-        };
         let bad = aig_bad.to_lit();
         for v in inputs.iter().chain(latchs.iter()).chain(primes.iter()) {
             simp_solver.set_frozen(*v, true);
@@ -53,8 +49,7 @@ impl Model {
             logic.push(*c);
         }
         logic.push(aig_bad);
-        let trans = aig.get_optimized_cnf(&logic);
-        // let trans = aig.get_cnf();
+        let trans = aig.get_encoded_cnf(&logic, encoding);
         for tran in trans.iter() {
             simp_solver.add_clause(tran);
         }
@@ -70,6 +65,12 @@ impl Model {
         simp_solver.eliminate(true);
         let trans = simp_solver.clauses();
 
+        let symbols = aig
+            .symbols
+            .nodes
+            .iter()
+            .map(|(id, name)| (Var::new(*id), name.clone()))
+            .collect();
         let mut next_map = HashMap::new();
         let mut previous_map = HashMap::new();
         for (l, p) in latchs.iter().zip(primes.iter()) {
@@ -84,6 +85,7 @@ impl Model {
             constraints,
             bad,
             trans,
+            symbols,
             num_var: simp_solver.num_var(),
             next_map,
             previous_map,
@@ -119,6 +121,14 @@ impl Model {
         true
     }
 
+    pub fn var_name(&self, var: Var) -> Option<&str> {
+        self.symbols.get(&var).map(|s| s.as_str())
+    }
+
+    pub fn lits_display(&self, lits: &[Lit]) -> String {
+        lits_display(lits, |var| self.var_name(var))
+    }
+
     pub fn load_trans(&self, solver: &mut Solver) {
         while solver.num_var() < self.num_var {
             solver.new_var();
@@ -128,3 +138,23 @@ impl Model {
         }
     }
 }
+
+/// Displays the literals by the names of their variables, as given by
+/// `var_name`, or by their numbers.
+pub fn lits_display<'a, F: Fn(Var) -> Option<&'a str>>(lits: &[Lit], var_name: F) -> String {
+    let lits: Vec<String> = lits
+        .iter()
+        .map(|l| {
+            let name = match var_name(l.var()) {
+                Some(name) => name.to_string(),
+                None => l.var().to_string(),
+            };
+            if l.polarity() {
+                name
+            } else {
+                format!("-{}", name)
+            }
+        })
+        .collect();
+    format!("[{}]", lits.join(", "))
+}
@@ -0,0 +1,180 @@
+use crate::model::Model;
+use aig::{Aig, AigEdge, AigNodeId, AigSimulator, CnfEncoding};
+use logic_form::{Lit, Var};
+use minisat::{SatResult, Solver};
+use std::collections::{HashMap, HashSet};
+
+const REGCORR_SIM_CYCLES: usize = 32;
+
+/// Candidate classes of equivalent latchs. Latchs are identified by their
+/// input node, node 0 stands for the constant false.
+struct LatchClasses {
+    classes: Vec<Vec<AigNodeId>>,
+    phase: HashMap<AigNodeId, bool>,
+}
+
+impl LatchClasses {
+    /// Builds the classes from random sequential simulation starting at the
+    /// initial state. Uninitialized latchs are not candidates.
+    fn from_simulation(aig: &Aig) -> Self {
+        let mut simulator = AigSimulator::new(aig, 0);
+        let mut signatures = vec![Vec::with_capacity(REGCORR_SIM_CYCLES); aig.latchs.len()];
+        for _ in 0..REGCORR_SIM_CYCLES {
+            for (signature, value) in signatures.iter_mut().zip(simulator.state()) {
+                signature.push(*value);
+            }
+            simulator.step_random();
+        }
+        let mut phase = HashMap::from([(0, false)]);
+        let mut map: HashMap<Vec<u64>, Vec<AigNodeId>> =
+            HashMap::from([(vec![0; REGCORR_SIM_CYCLES], vec![0])]);
+        for (l, mut signature) in aig.latchs.iter().zip(signatures) {
+            if l.init.is_none() {
+                continue;
+            }
+            let p = signature[0] & 1 == 1;
+            if p {
+                signature.iter_mut().for_each(|w| *w = !*w);
+            }
+            phase.insert(l.input, p);
+            map.entry(signature).or_default().push(l.input);
+        }
+        let mut classes: Vec<Vec<AigNodeId>> = map.into_values().filter(|c| c.len() > 1).collect();
+        for class in classes.iter_mut() {
+            class.sort();
+        }
+        Self { classes, phase }
+    }
+
+    fn lit(&self, id: AigNodeId) -> Lit {
+        Lit::new(Var::new(id), !self.phase[&id])
+    }
+
+    /// Splits every class by the values of a counterexample.
+    fn refine(&mut self, value: &HashMap<AigNodeId, bool>) {
+        for i in 0..self.classes.len() {
+            let value = |id: &AigNodeId| value[id] != self.phase[id];
+            let head = value(&self.classes[i][0]);
+            let (same, diff): (Vec<AigNodeId>, Vec<AigNodeId>) =
+                self.classes[i].iter().partition(|id| value(id) == head);
+            if diff.is_empty() {
+                continue;
+            }
+            self.classes[i] = same;
+            self.classes.push(diff);
+        }
+        self.classes.retain(|c| c.len() > 1);
+    }
+}
+
+/// Van Eijk style register correspondence. Candidate classes of equivalent
+/// latchs come from random simulation and are refined until they are
+/// inductive over the transition relation of the model, assuming all of them
+/// in the current state. The surviving latchs are merged into the smallest
+/// latch of their class, or into the constant. Returns the number of removed
+/// latchs.
+pub fn register_correspondence(aig: &mut Aig) -> usize {
+    let mut classes = LatchClasses::from_simulation(aig);
+    if classes.classes.is_empty() {
+        return 0;
+    }
+    let model = Model::from_aig(aig, AigEdge::constant_edge(false), CnfEncoding::default());
+    let next = |lit: Lit| {
+        if lit.var() == Var::new(0) {
+            lit
+        } else {
+            model.lit_next(lit)
+        }
+    };
+    loop {
+        let mut solver = Solver::new();
+        let false_lit: Lit = solver.new_var().into();
+        solver.add_clause(&[!false_lit]);
+        model.load_trans(&mut solver);
+        for class in classes.classes.iter() {
+            let repr = classes.lit(class[0]);
+            for member in class[1..].iter() {
+                let member = classes.lit(*member);
+                solver.add_clause(&[!repr, member]);
+                solver.add_clause(&[repr, !member]);
+            }
+        }
+        let mut refined = false;
+        let mut i = 0;
+        while i < classes.classes.len() {
+            let mut j = 1;
+            while j < classes.classes[i].len() {
+                let repr = next(classes.lit(classes.classes[i][0]));
+                let member = next(classes.lit(classes.classes[i][j]));
+                let mut cex = None;
+                for assumps in [[repr, !member], [!repr, member]] {
+                    if let SatResult::Sat(model) = solver.solve(&assumps) {
+                        let value: HashMap<AigNodeId, bool> = classes
+                            .classes
+                            .iter()
+                            .flatten()
+                            .map(|id| (*id, model.lit_value(next(Var::new(*id).lit()))))
+                            .collect();
+                        cex = Some(value);
+                        break;
+                    }
+                }
+                match cex {
+                    Some(value) => {
+                        classes.refine(&value);
+                        refined = true;
+                        j = 1;
+                    }
+                    None => j += 1,
+                }
+                if i >= classes.classes.len() {
+                    break;
+                }
+            }
+            i += 1;
+        }
+        if !refined {
+            break;
+        }
+    }
+    let mut removed = HashSet::new();
+    for class in classes.classes.iter() {
+        let repr = class[0];
+        for member in class[1..].iter() {
+            let compl = classes.phase[member] != classes.phase[&repr];
+            aig.merge_fe_node((*member).into(), AigEdge::new(repr, compl));
+            removed.insert(*member);
+        }
+    }
+    aig.latchs.retain(|l| !removed.contains(&l.input));
+    removed.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regcorr_merges_constant_and_equivalent_latchs() {
+        #[rustfmt::skip]
+        let mut aig = Aig::from_reader(concat!(
+            "aag 5 1 3 3 1\n",
+            "2\n",
+            "4 10 0\n",
+            "6 2 0\n",
+            "8 2 0\n",
+            "10\n",
+            "6\n",
+            "8\n",
+            "10 4 2\n",
+        ).as_bytes()).unwrap();
+
+        assert_eq!(register_correspondence(&mut aig), 2);
+        assert_eq!(aig.latchs.len(), 1);
+        assert_eq!(aig.outputs[1], aig.outputs[2]);
+        let mut simulator = AigSimulator::new(&aig, 0);
+        simulator.set_state(&[!0]);
+        simulator.step(&[!0]);
+        assert_eq!(simulator.edge_value(aig.outputs[0]), 0);
+    }
+}
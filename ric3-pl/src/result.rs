@@ -0,0 +1,68 @@
+use aig::AigTrace;
+use logic_form::Cube;
+use std::fmt::{self, Display};
+
+/// Why a check stops without a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownReason {
+    Interrupted,
+    TimeLimit,
+    MemoryLimit,
+    /// A counterexample is found which does not replay on the model file.
+    InvalidCounterexample,
+}
+
+impl Display for UnknownReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnknownReason::Interrupted => write!(f, "interrupted"),
+            UnknownReason::TimeLimit => write!(f, "time limit"),
+            UnknownReason::MemoryLimit => write!(f, "memory limit"),
+            UnknownReason::InvalidCounterexample => write!(f, "invalid counterexample"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum CheckResult {
+    /// The property holds. The inductive invariant is given by its lemmas,
+    /// the cubes of the checked aig it blocks.
+    Safe(Vec<Cube>),
+    /// The property fails, with a counterexample of the model file.
+    Unsafe(AigTrace),
+    /// The check stops without a result. The bad is unreachable within
+    /// `proven_bound` steps, if any.
+    Unknown {
+        reason: UnknownReason,
+        proven_bound: Option<usize>,
+    },
+}
+
+impl CheckResult {
+    /// The exit code of the hwmcc convention: 20 if safe, 10 if unsafe and
+    /// 0 if unknown.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CheckResult::Safe(_) => 20,
+            CheckResult::Unsafe(_) => 10,
+            CheckResult::Unknown { .. } => 0,
+        }
+    }
+}
+
+impl Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckResult::Safe(_) => write!(f, "true"),
+            CheckResult::Unsafe(_) => write!(f, "false"),
+            CheckResult::Unknown {
+                reason,
+                proven_bound: Some(bound),
+            } => write!(f, "unknown ({reason}, proven bound: {bound})"),
+            CheckResult::Unknown {
+                reason,
+                proven_bound: None,
+            } => write!(f, "unknown ({reason})"),
+        }
+    }
+}
@@ -27,6 +27,13 @@ impl Ic3 {
             }
         }
         println!("inductive invariant verified with {num} lemmas!");
+        if self.share.args.verbose {
+            for i in invariant..self.frames.len() {
+                for cube in self.frames[i].iter() {
+                    println!("{}", self.share.model.lits_display(&!cube));
+                }
+            }
+        }
         true
     }
 }
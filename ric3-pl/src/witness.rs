@@ -0,0 +1,40 @@
+use crate::{
+    cec::{bits, load},
+    Args, Error,
+};
+use aig::AigWitness;
+
+/// Simulates the `--check-witness` file on the model and checks that it
+/// reaches the claimed bads. With `-v`, the latches of every cycle are
+/// printed by name.
+pub fn check_witness(args: &Args) -> Result<bool, Error> {
+    let model = load(args.model.as_ref().unwrap())?;
+    let file = args.check_witness.as_ref().unwrap();
+    let witness =
+        AigWitness::from_file(file, &model).map_err(|e| Error::Witness(file.clone(), e))?;
+    if args.verbose {
+        for (i, step) in model.simulate_trace(&witness.trace).iter().enumerate() {
+            let latchs: Vec<String> = model
+                .latchs
+                .iter()
+                .zip(step.latchs.iter())
+                .enumerate()
+                .map(|(j, (l, v))| match model.node_name(l.input) {
+                    Some(name) => format!("{name}={}", *v as u8),
+                    None => format!("l{j}={}", *v as u8),
+                })
+                .collect();
+            println!("cycle {i}: {}", latchs.join(" "));
+            println!("  outputs: {}", bits(&step.outputs));
+            if !model.bads.is_empty() {
+                println!("  bads: {}", bits(&step.bads));
+            }
+            if !step.constraints {
+                println!("  constraints violated");
+            }
+        }
+    }
+    let res = model.check_witness(&witness);
+    println!("result: {res}");
+    Ok(res)
+}
@@ -4,8 +4,12 @@ use aig::Aig;
 use logic_form::Cube;
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
+use std::rc::Rc;
 
 pub struct BasicShare {
+    /// The model as read from the model file, which counterexamples are
+    /// translated to.
+    pub origin: Aig,
     pub aig: Aig,
     pub args: Args,
     pub model: Model,
@@ -17,15 +21,25 @@ pub struct ProofObligation {
     pub frame: usize,
     pub cube: Cube,
     pub depth: usize,
-    pub successor: Option<Cube>,
+    /// Values of the inputs under which the cube reaches its successor, or
+    /// the bad if it has none.
+    pub inputs: Cube,
+    pub successor: Option<Rc<ProofObligation>>,
 }
 
 impl ProofObligation {
-    pub fn new(frame: usize, cube: Cube, depth: usize, successor: Option<Cube>) -> Self {
+    pub fn new(
+        frame: usize,
+        cube: Cube,
+        depth: usize,
+        inputs: Cube,
+        successor: Option<Rc<ProofObligation>>,
+    ) -> Self {
         Self {
             frame,
             cube,
             depth,
+            inputs,
             successor,
         }
     }
@@ -78,6 +92,11 @@ impl ProofObligationQueue {
         self.obligations.is_empty()
     }
 
+    pub fn clear(&mut self) {
+        self.obligations.clear();
+        self.num.clear();
+    }
+
     pub fn statistic(&self) {
         println!("{:?}", self.num);
     }
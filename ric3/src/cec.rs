@@ -0,0 +1,36 @@
+use crate::{Args, Error};
+use aig::{Aig, CecResult, MiterInputs};
+
+pub(crate) fn load(file: &str) -> Result<Aig, Error> {
+    Aig::from_file(file).map_err(|e| Error::Aiger(file.to_string(), e))
+}
+
+pub(crate) fn bits(values: &[bool]) -> String {
+    values.iter().map(|v| if *v { '1' } else { '0' }).collect()
+}
+
+/// Checks the combinational equivalence of the model and the `--cec` model.
+/// If they differ, the values of the inputs and latches of the model which
+/// distinguish them are printed.
+pub fn cec(args: &Args) -> Result<bool, Error> {
+    let model = load(args.model.as_ref().unwrap())?;
+    let other = load(args.cec.as_ref().unwrap())?;
+    let inputs = if args.cec_by_name {
+        MiterInputs::ByName
+    } else {
+        MiterInputs::ByPosition
+    };
+    match model.cec(&other, inputs).map_err(Error::Miter)? {
+        CecResult::Equivalent => {
+            println!("result: true");
+            Ok(true)
+        }
+        CecResult::NotEquivalent(values) => {
+            println!("result: false");
+            let (inputs, latchs) = values.split_at(model.inputs.len());
+            println!("inputs: {}", bits(inputs));
+            println!("latchs: {}", bits(latchs));
+            Ok(false)
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use aig::CnfEncoding;
 use clap::Parser;
 
 #[derive(Parser, Debug, Clone)]
@@ -33,6 +34,75 @@ pub struct Args {
     /// save frames
     #[arg(long, default_value_t = false)]
     pub save_frames: bool,
+
+    /// remove constant latchs
+    #[arg(long, default_value_t = false)]
+    pub latch_reduce: bool,
+
+    /// register correspondence
+    #[arg(long, default_value_t = false)]
+    pub regcorr: bool,
+
+    /// cone of influence reduction
+    #[arg(long, default_value_t = false)]
+    pub coi: bool,
+
+    /// sat sweeping before building the model
+    #[arg(long, default_value_t = false)]
+    pub fraig: bool,
+
+    /// dag-aware rewriting and and-tree balancing before building the model
+    #[arg(long, default_value_t = false)]
+    pub rewrite: bool,
+
+    /// cnf encoding of the transition relation: tseitin, block or cut
+    #[arg(long, default_value = "tseitin")]
+    pub cnf: CnfEncoding,
+
+    /// random simulation of 64 traces for up to the given number of cycles
+    /// before ic3, 0 disables it
+    #[arg(long, default_value_t = 0)]
+    pub sim: usize,
+
+    /// check the combinational equivalence with another aiger file instead,
+    /// latches are cut points, exits with 20 if equivalent and 10 if not
+    #[arg(long)]
+    pub cec: Option<String>,
+
+    /// match the inputs and latches of the cec models by name instead of
+    /// position
+    #[arg(long, default_value_t = false, requires("cec"))]
+    pub cec_by_name: bool,
+
+    /// simulate a witness or stimulus file on the model instead and check
+    /// that it reaches a bad, exits with 20 if it does and 10 if not, -v
+    /// prints the latches of every cycle
+    #[arg(long)]
+    pub check_witness: Option<String>,
+
+    /// write the witness of an unsafe result to the given file in the aiger
+    /// witness format
+    #[arg(long)]
+    pub witness: Option<String>,
+
+    /// time limit in seconds, the check stops with an unknown result when it
+    /// is reached
+    #[arg(long)]
+    pub time_limit: Option<u64>,
+
+    /// memory limit in megabytes, the check stops with an unknown result when
+    /// it is reached
+    #[arg(long)]
+    pub memory_limit: Option<u64>,
+
+    /// index of the property to check, among the bads, or the outputs if
+    /// there are no bads
+    #[arg(long, default_value_t = 0)]
+    pub property: usize,
+
+    /// check every property in turn, reusing the lemmas of earlier checks
+    #[arg(long, default_value_t = false, conflicts_with("property"))]
+    pub all_properties: bool,
 }
 
 impl Default for Args {
@@ -128,6 +128,9 @@ impl Ic3 {
         });
         let mut i = 0;
         while i < cube.len() {
+            if self.should_stop() {
+                break;
+            }
             let mut removed_cube = cube.clone();
             removed_cube.remove(i);
             let res = if simple {
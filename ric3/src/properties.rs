@@ -0,0 +1,74 @@
+use crate::{Args, CheckResult, Error, Ic3};
+use aig::Aig;
+use logic_form::{Cube, Lit, Var};
+use std::collections::HashSet;
+
+impl Ic3 {
+    /// Returns the lemmas of the invariant, or of frames 1 and up if none
+    /// has been found, as cubes over the variables of the model file. They
+    /// do not depend on the bad, so they hold for every property in all
+    /// states reachable within one step.
+    pub fn lemmas(&self) -> Vec<Cube> {
+        let var_map = &self.share.aig.var_map;
+        let first = self.frames.iter().position(|f| f.is_empty()).unwrap_or(1);
+        self.frames[first..]
+            .iter()
+            .flatten()
+            .filter_map(|cube| {
+                let mut lemma: Cube = cube
+                    .iter()
+                    .map(|lit| {
+                        let var = var_map.origin(lit.var().into())?;
+                        Some(Lit::new(Var::new(var), lit.polarity()))
+                    })
+                    .collect::<Option<Cube>>()?;
+                lemma.sort_by_key(|lit| lit.var());
+                Some(lemma)
+            })
+            .collect()
+    }
+
+    /// Adds lemmas of another check of the same model file, see
+    /// [`Ic3::lemmas`], to frame 1. Lemmas on latches outside the checked
+    /// aig are dropped.
+    pub fn add_lemmas<'a, I: IntoIterator<Item = &'a Cube>>(&mut self, lemmas: I) {
+        let aig = &self.share.aig;
+        for lemma in lemmas {
+            let lemma: Option<Cube> = lemma
+                .iter()
+                .map(|lit| {
+                    let id = aig.var_map.node(lit.var().into())?;
+                    aig.nodes[id]
+                        .is_latch_input()
+                        .then(|| Lit::new(Var::new(id), lit.polarity()))
+                })
+                .collect();
+            self.reused_lemmas.extend(lemma);
+        }
+    }
+}
+
+/// Checks every property of the model file in turn, each with its own ic3 on
+/// the model reduced to the property. The lemmas of every check are reused
+/// by the later ones.
+pub fn check_all(args: Args) -> Result<Vec<CheckResult>, Error> {
+    let model = args.model.as_ref().unwrap();
+    let num_properties = Aig::from_file(model)
+        .map_err(|e| Error::Aiger(model.clone(), e))?
+        .properties()
+        .len();
+    let mut lemmas = HashSet::new();
+    let mut results = Vec::with_capacity(num_properties);
+    for property in 0..num_properties {
+        let mut ic3 = Ic3::new(Args {
+            property,
+            ..args.clone()
+        })?;
+        ic3.add_lemmas(&lemmas);
+        let res = ic3.check();
+        println!("property {property}: {res}");
+        lemmas.extend(ic3.lemmas());
+        results.push(res);
+    }
+    Ok(results)
+}
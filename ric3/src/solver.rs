@@ -1,5 +1,6 @@
 use super::{basic::BasicShare, frames::Frames};
 use crate::Ic3;
+use aig::TernaryValue;
 use logic_form::{Clause, Cube, Lit, Var};
 use minisat::{SatResult, Solver};
 use std::{mem::take, sync::Arc, time::Instant};
@@ -96,19 +97,17 @@ impl Ic3Solver {
 }
 
 impl Ic3 {
-    pub fn get_bad(&mut self) -> Option<Cube> {
-        let bad = if self.share.aig.bads.is_empty() {
-            self.share.aig.outputs[0]
-        } else {
-            self.share.aig.bads[0]
-        };
-        if let SatResult::Sat(_) = self.solvers.last_mut().unwrap().solve(&[bad.to_lit()]) {
+    /// Returns a state of the last frame reaching the bad and the values of
+    /// the inputs under which it does.
+    pub fn get_bad(&mut self) -> Option<(Cube, Cube)> {
+        if let SatResult::Sat(_) = self.solvers.last_mut().unwrap().solve(&self.share.bad) {
             self.statistic.num_get_bad_state += 1;
             let model = unsafe { self.solvers.last().unwrap().solver.get_model() };
             let bad = self.share.bad.clone();
+            let inputs = self.model_inputs(&model);
             let cex = self.minimal_predecessor(&bad, model);
             // let cex = self.generalize_by_ternary_simulation(model, &bad);
-            return Some(cex);
+            return Some((cex, inputs));
         }
         None
     }
@@ -221,6 +220,11 @@ impl Ic3 {
         // self.generalize_by_ternary_simulation(model, &unblock.assumption)
     }
 
+    pub fn unblocked_model_inputs(&self, unblock: &BlockResultNo) -> Cube {
+        let model = unsafe { self.solvers[unblock.solver_idx].solver.get_model() };
+        self.model_inputs(&model)
+    }
+
     pub fn unblocked_model_lit_value(&self, unblock: &BlockResultNo, lit: Lit) -> bool {
         unsafe { self.solvers[unblock.solver_idx].solver.get_model() }.lit_value(lit)
     }
@@ -229,6 +233,8 @@ impl Ic3 {
 pub struct Lift {
     solver: Solver,
     num_act: usize,
+    /// Whether a node is in the fanin cone of the constraints.
+    constraint_cone: Vec<bool>,
 }
 
 impl Lift {
@@ -241,11 +247,75 @@ impl Lift {
         let false_lit: Lit = solver.new_var().into();
         solver.add_clause(&[!false_lit]);
         share.model.load_trans(&mut solver);
-        Self { solver, num_act: 0 }
+        let constraint_cone = share.aig.fanin_logic_cone(&share.aig.constraints);
+        Self {
+            solver,
+            num_act: 0,
+            constraint_cone,
+        }
     }
 }
 
 impl Ic3 {
+    /// Values of the inputs in a model, by position.
+    fn model_inputs(&self, model: &minisat::Model) -> Cube {
+        self.share
+            .aig
+            .inputs
+            .iter()
+            .map(|input| {
+                let lit: Lit = Var::from(*input).into();
+                if model.lit_value(lit) {
+                    lit
+                } else {
+                    !lit
+                }
+            })
+            .collect()
+    }
+
+    /// Keeps more latches of the constraint cone, in the order of `latchs`,
+    /// until ternary simulation under the inputs of the model shows that
+    /// the constraints hold in all states of the kept latches. The
+    /// constraints are hard in the lifting solver, so its core alone may
+    /// keep states which violate them.
+    fn keep_constraints(&self, model: &minisat::Model, latchs: &Cube, keep: &mut [bool]) {
+        let aig = &self.share.aig;
+        let value = |id: usize| TernaryValue::from(model.lit_value(Var::from(id).into()));
+        let mut kept = vec![false; aig.num_nodes()];
+        for (l, k) in latchs.iter().zip(keep.iter()) {
+            kept[usize::from(l.var())] = *k;
+        }
+        let inputs: Vec<TernaryValue> = aig.inputs.iter().map(|i| value(*i)).collect();
+        let latch_values: Vec<TernaryValue> = aig
+            .latchs
+            .iter()
+            .map(|l| {
+                if kept[l.input] {
+                    value(l.input)
+                } else {
+                    TernaryValue::X
+                }
+            })
+            .collect();
+        let mut simulation = aig.ternary_simulate(&inputs, &latch_values);
+        let hold = |simulation: &[TernaryValue]| {
+            aig.constraints
+                .iter()
+                .all(|c| simulation[c.node_id()].not_if(c.compl()) == TernaryValue::True)
+        };
+        for (l, k) in latchs.iter().zip(keep.iter_mut()) {
+            if hold(&simulation) {
+                break;
+            }
+            let id = usize::from(l.var());
+            if !*k && self.lift.constraint_cone[id] {
+                simulation = aig.update_ternary_simulate(simulation, id, l.polarity().into());
+                *k = true;
+            }
+        }
+    }
+
     pub fn minimal_predecessor(&mut self, successor: &Cube, model: minisat::Model) -> Cube {
         self.lift.num_act += 1;
         if self.lift.num_act > 1000 {
@@ -256,13 +326,7 @@ impl Ic3 {
         let mut cls = !successor;
         cls.push(!act);
         self.lift.solver.add_clause(&cls);
-        for input in self.share.aig.inputs.iter() {
-            let mut lit: Lit = Var::from(*input).into();
-            if !model.lit_value(lit) {
-                lit = !lit;
-            }
-            assumption.push(lit);
-        }
+        assumption.extend_from_slice(&self.model_inputs(&model));
         let mut latchs = Cube::new();
         for latch in &self.share.aig.latchs {
             let mut lit: Lit = Var::from(latch.input).into();
@@ -279,7 +343,15 @@ impl Ic3 {
         let res: Cube = match self.lift.solver.solve(&assumption) {
             SatResult::Sat(_) => panic!(),
             SatResult::Unsat(conflict) => {
-                latchs.into_iter().filter(|l| conflict.has(!*l)).collect()
+                let mut keep: Vec<bool> = latchs.iter().map(|l| conflict.has(!*l)).collect();
+                if !self.share.aig.constraints.is_empty() {
+                    self.keep_constraints(&model, &latchs, &mut keep);
+                }
+                latchs
+                    .into_iter()
+                    .zip(keep)
+                    .filter_map(|(l, k)| k.then_some(l))
+                    .collect()
             }
         };
         self.lift.solver.release_var(!act);
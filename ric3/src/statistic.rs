@@ -9,6 +9,7 @@ use std::{
 #[derive(Debug, Default)]
 pub struct Statistic {
     case: Case,
+    property: Case,
     time: RunningTime,
     pub num_blocked: usize,
     pub num_mic: usize,
@@ -17,6 +18,10 @@ pub struct Statistic {
     pub mic_drop: SuccessRate,
     pub num_ctg_down: usize,
     pub num_get_bad_state: usize,
+    pub num_constant_latchs: usize,
+    pub num_regcorr_latchs: usize,
+    pub num_coi_dropped_inputs: usize,
+    pub num_coi_dropped_latchs: usize,
     pub average_mic_cube_len: StatisticAverage,
 
     pub simple_mic_time: Duration,
@@ -28,12 +33,19 @@ pub struct Statistic {
 }
 
 impl Statistic {
-    pub fn new(mut case: &str) -> Self {
+    /// Statistics of the check of `property` of the model file `case`,
+    /// shown with the name of the property if it has one.
+    pub fn new(mut case: &str, property: usize, name: Option<&str>) -> Self {
         if let Some((_, c)) = case.rsplit_once('/') {
             case = c;
         }
+        let property = match name {
+            Some(name) => format!("{property} ({name})"),
+            None => property.to_string(),
+        };
         Self {
             case: Case(case.to_string()),
+            property: Case(property),
             ..Default::default()
         }
     }
@@ -0,0 +1,42 @@
+use crate::{basic::ProofObligation, Ic3};
+use aig::{AigTrace, AigWitness};
+use logic_form::Var;
+
+impl Ic3 {
+    /// Builds the concrete trace of `share.aig` from a proof obligation in
+    /// frame 0 and its chain of successors. Uninitialized latches which are
+    /// not in the cube start at 0.
+    pub(crate) fn po_trace(&self, po: &ProofObligation) -> AigTrace {
+        let init = self
+            .share
+            .aig
+            .latchs
+            .iter()
+            .map(|l| match l.init {
+                Some(init) => init,
+                None => po
+                    .cube
+                    .iter()
+                    .any(|lit| lit.var() == Var::from(l.input) && lit.polarity()),
+            })
+            .collect();
+        let mut inputs = Vec::new();
+        let mut po = Some(po);
+        while let Some(p) = po {
+            inputs.push(p.inputs.iter().map(|lit| lit.polarity()).collect());
+            po = p.successor.as_deref();
+        }
+        AigTrace { init, inputs }
+    }
+
+    /// Translates a trace of `share.aig` to the model file and replays it
+    /// there. Returns `None` if it does not reach the checked property.
+    pub(crate) fn original_trace(&self, trace: &AigTrace) -> Option<AigTrace> {
+        let origin = &self.share.origin;
+        let witness = AigWitness {
+            bads: vec![self.share.args.property],
+            trace: self.share.aig.map_trace(trace, origin),
+        };
+        origin.check_witness(&witness).then_some(witness.trace)
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+/// A shift register from the input whose last latch is the bad, which is
+/// reached in cycle 2.
+#[rustfmt::skip]
+const UNSAFE: &str = concat!(
+    "aag 3 1 2 0 0 1\n",
+    "2\n",
+    "4 2\n",
+    "6 4\n",
+    "6\n",
+);
+
+/// A latch which keeps its initial 0 and is the bad.
+#[rustfmt::skip]
+const SAFE: &str = concat!(
+    "aag 2 1 1 0 0 1\n",
+    "2\n",
+    "4 4\n",
+    "4\n",
+);
+
+/// The shift register with a second bad which never holds.
+#[rustfmt::skip]
+const MIXED: &str = concat!(
+    "aag 4 1 2 0 1 2\n",
+    "2\n",
+    "4 2\n",
+    "6 4\n",
+    "6\n",
+    "8\n",
+    "8 4 5\n",
+);
+
+/// A latch which turns 1 after the first cycle and is the bad, and a latch
+/// of the input, which the constraint requires to be 1 once the bad latch is.
+/// Lifting must keep the input latch for the trace to meet the constraint.
+#[rustfmt::skip]
+const CONSTRAINED: &str = concat!(
+    "aag 4 1 2 0 1 1 1\n",
+    "2\n",
+    "4 1\n",
+    "6 2\n",
+    "4\n",
+    "9\n",
+    "8 4 7\n",
+);
+
+/// An and of two inputs, and the same and with the fanins swapped.
+#[rustfmt::skip]
+const AND: [&str; 2] = [
+    concat!("aag 3 2 0 1 1\n", "2\n", "4\n", "6\n", "6 2 4\n"),
+    concat!("aag 3 2 0 1 1\n", "2\n", "4\n", "6\n", "6 4 2\n"),
+];
+
+/// An or of two inputs.
+#[rustfmt::skip]
+const OR: &str = concat!("aag 3 2 0 1 1\n", "2\n", "4\n", "7\n", "6 3 5\n");
+
+fn file(name: &str, content: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, content).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn ic3(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ic3"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn check_witness(model: &str, witness: &str) -> bool {
+    let output = ic3(&[model, "--check-witness", witness]);
+    let res = String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("result: true");
+    assert_eq!(output.status.code(), Some(if res { 20 } else { 10 }));
+    res
+}
+
+#[test]
+fn safe_exit_code() {
+    let model = file("safe.aag", SAFE);
+    assert_eq!(ic3(&[&model]).status.code(), Some(20));
+}
+
+#[test]
+fn unsafe_exit_code_and_witness() {
+    let model = file("unsafe.aag", UNSAFE);
+    let witness = file("unsafe.wit", "");
+    assert_eq!(
+        ic3(&[&model, "--witness", &witness]).status.code(),
+        Some(10)
+    );
+    assert!(fs::read_to_string(&witness).unwrap().starts_with("1\nb0\n"));
+    assert!(check_witness(&model, &witness));
+}
+
+#[test]
+fn constrained_witness() {
+    let model = file("constrained.aag", CONSTRAINED);
+    let witness = file("constrained.wit", "");
+    assert_eq!(
+        ic3(&[&model, "--witness", &witness]).status.code(),
+        Some(10)
+    );
+    assert!(check_witness(&model, &witness));
+}
+
+#[test]
+fn invalid_witness_is_rejected() {
+    let model = file("invalid.aag", UNSAFE);
+    let witness = file("invalid.wit", "1\nb0\n00\n0\n0\n0\n.\n");
+    assert!(!check_witness(&model, &witness));
+    let witness = file("safe.wit", "0\nb0\n.\n");
+    assert_eq!(
+        ic3(&[&model, "--check-witness", &witness]).status.code(),
+        Some(1)
+    );
+}
+
+#[test]
+fn all_properties_witness() {
+    let model = file("mixed.aag", MIXED);
+    let witness = file("mixed.wit", "");
+    let output = ic3(&[&model, "--all-properties", "--witness", &witness]);
+    assert_eq!(output.status.code(), Some(10));
+    assert_eq!(
+        fs::read_to_string(&witness).unwrap(),
+        "1\nb0\n00\n1\n0\n0\n.\n0\n"
+    );
+    assert!(check_witness(&model, &witness));
+}
+
+#[test]
+fn missing_property() {
+    let model = file("missing.aag", SAFE);
+    let output = ic3(&[&model, "--property", "1"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("property 1 does not exist"));
+}
+
+#[test]
+fn cec_exit_codes() {
+    let and = file("cec_and.aag", AND[0]);
+    let swapped = file("cec_swapped.aag", AND[1]);
+    let or = file("cec_or.aag", OR);
+    assert_eq!(ic3(&[&and, "--cec", &swapped]).status.code(), Some(20));
+    let output = ic3(&[&and, "--cec", &or]);
+    assert_eq!(output.status.code(), Some(10));
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("result: false"));
+}